@@ -49,7 +49,5 @@ fn lnlik(params: &[f64], data: &HashMap<&str, Data>) {
 
     beta!(p; 2., 2.);
 
-    for i in heads {
-        binomial!(*i; *trials, p);
-    }
+    binomial!(heads; trials, p);
 }
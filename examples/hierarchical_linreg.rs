@@ -53,10 +53,8 @@ fn lnlik(params: &[f64], data: &HashMap<&str, Data>) {
     let x2 = unpack!(data["x2"], FloatArray);
     let y2 = unpack!(data["y2"], FloatArray);
 
-    for i in 0..x1.len() {
-        normal!(y1[i]; x1[i] * s1 + i1, 0.5_f64);
-        normal!(y2[i]; x2[i] * s2 + i2, 0.5_f64);
-    }
+    normal!(y1; x1 * s1 + i1, 0.5_f64);
+    normal!(y2; x2 * s2 + i2, 0.5_f64);
 
     normal!(i1; ih, 2_f64);
     normal!(i2; ih, 2_f64);
@@ -36,7 +36,6 @@ fn lnlik(params: &[f64], data: &[&[f64]]) {
     laplace!(b; 2_f64, 1_f64);
     exponential!(s; 1_f64);
 
-    for i in 0..500 {
-        normal!(y[i] ; x[i] * m + b, s.exp());
-    }
+    let mu = x.iter().map(|xi| xi * m + b).collect::<Vec<_>>();
+    normal!(y; mu, s.exp());
 }
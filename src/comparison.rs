@@ -0,0 +1,442 @@
+//! Model comparison based on the pointwise log likelihood of posterior draws.
+
+use crate::functions::log_sum_exp;
+
+/// Evaluates a pointwise log likelihood `f`, such as the one generated by `#[model(..., pointwise)]`,
+/// at every draw in `samples`.
+///
+/// `samples` has one row per parameter, as returned by `Sampler::sample`. The result has one row
+/// per observation, holding the log likelihood of that observation at every draw.
+///
+/// Panics if `f` does not return `n_obs` values at every draw, e.g. because some observations
+/// share a single likelihood statement, which would make the leave-one-out estimates meaningless.
+pub fn pointwise_log_likelihood<F, S>(
+    f: F,
+    samples: &[Vec<f64>],
+    data: S,
+    n_obs: usize,
+) -> Vec<Vec<f64>>
+where
+    F: Fn(&[f64], S) -> Vec<f64>,
+    S: Copy,
+{
+    let n_draws = samples.first().map_or(0, |x| x.len());
+
+    let draws = (0..n_draws)
+        .map(|i| f(&samples.iter().map(|x| x[i]).collect::<Vec<_>>(), data))
+        .collect::<Vec<_>>();
+
+    if let Some(x) = draws.iter().find(|x| x.len() != n_obs) {
+        panic!(
+            "Expected {} pointwise log likelihoods, one per observation, but got {}.",
+            n_obs,
+            x.len()
+        );
+    }
+
+    (0..n_obs)
+        .map(|j| draws.iter().map(|x| x[j]).collect())
+        .collect()
+}
+
+fn mean(x: &[f64]) -> f64 {
+    x.iter().sum::<f64>() / x.len() as f64
+}
+
+fn var(x: &[f64]) -> f64 {
+    let m = mean(x);
+    x.iter().map(|i| (i - m).powi(2)).sum::<f64>() / (x.len() as f64 - 1.)
+}
+
+/// Pointwise estimates of the expected log predictive density (elpd), from either `psis_loo` or
+/// `waic`.
+#[derive(Debug, Clone)]
+pub struct Elpd {
+    /// The elpd of each observation.
+    pub pointwise: Vec<f64>,
+    /// The effective number of parameters of each observation.
+    pub p_pointwise: Vec<f64>,
+    /// The Pareto shape diagnostic k̂ of each observation. Only available for `psis_loo`.
+    pub pareto_k: Option<Vec<f64>>,
+}
+
+impl Elpd {
+    /// The total elpd.
+    pub fn elpd(&self) -> f64 {
+        self.pointwise.iter().sum()
+    }
+
+    /// The standard error of the total elpd. It cannot be estimated from a single observation, for
+    /// which it is 0.
+    pub fn se(&self) -> f64 {
+        let n = self.pointwise.len();
+        if n > 1 {
+            (n as f64 * var(&self.pointwise)).sqrt()
+        } else {
+            0.
+        }
+    }
+
+    /// The total effective number of parameters.
+    pub fn p(&self) -> f64 {
+        self.p_pointwise.iter().sum()
+    }
+
+    /// The elpd on the deviance scale, i.e. LOOIC or WAIC.
+    pub fn ic(&self) -> f64 {
+        -2. * self.elpd()
+    }
+
+    /// Indices of the observations whose k̂ is above `threshold`. Estimates for these are
+    /// unreliable; 0.7 is the usual threshold.
+    pub fn bad_k(&self, threshold: f64) -> Vec<usize> {
+        self.pareto_k
+            .iter()
+            .flatten()
+            .enumerate()
+            .filter(|(_, &k)| k > threshold)
+            .map(|(i, _)| i)
+            .collect()
+    }
+}
+
+/// Calculates the [widely applicable information
+/// criterion](https://en.wikipedia.org/wiki/Watanabe%E2%80%93Akaike_information_criterion) from
+/// the pointwise log likelihood, with one row per observation and one column per draw (as returned
+/// by `pointwise_log_likelihood`).
+pub fn waic(log_lik: &[Vec<f64>]) -> Elpd {
+    let (pointwise, p_pointwise) = log_lik
+        .iter()
+        .map(|ll| {
+            let lppd = log_sum_exp(ll) - (ll.len() as f64).ln();
+            let p = var(ll);
+            (lppd - p, p)
+        })
+        .unzip();
+
+    Elpd {
+        pointwise,
+        p_pointwise,
+        pareto_k: None,
+    }
+}
+
+/// Fits a generalized Pareto distribution to the sorted exceedances `x` using the empirical Bayes
+/// method of Zhang and Stephens (2009), returning the shape `k` and scale `sigma`. The shape is
+/// regularized towards 0.5 as in Vehtari et al. (2017).
+fn gpd_fit(x: &[f64]) -> (f64, f64) {
+    let n = x.len();
+    let prior = 3.;
+    let m = 30 + (n as f64).sqrt() as usize;
+    let xstar = x[((n as f64) / 4. + 0.5) as usize - 1];
+
+    let theta = (1..=m)
+        .map(|j| 1. / x[n - 1] + (1. - (m as f64 / (j as f64 - 0.5)).sqrt()) / prior / xstar)
+        .collect::<Vec<_>>();
+
+    let l_theta = theta
+        .iter()
+        .map(|&t| {
+            let k = mean(&x.iter().map(|&xi| (-t * xi).ln_1p()).collect::<Vec<_>>());
+            n as f64 * ((-t / k).ln() - k - 1.)
+        })
+        .collect::<Vec<_>>();
+
+    let lse = log_sum_exp(&l_theta);
+    let theta_hat = theta
+        .iter()
+        .zip(&l_theta)
+        .map(|(t, l)| t * (l - lse).exp())
+        .sum::<f64>();
+
+    let k = mean(
+        &x.iter()
+            .map(|&xi| (-theta_hat * xi).ln_1p())
+            .collect::<Vec<_>>(),
+    );
+    let sigma = -k / theta_hat;
+    let k = (n as f64 * k + 10. * 0.5) / (n as f64 + 10.);
+
+    (if k.is_nan() { f64::INFINITY } else { k }, sigma)
+}
+
+/// Pareto smoothed importance sampling (Vehtari et al., 2017).
+///
+/// Smooths the largest importance ratios by fitting a generalized Pareto distribution to them.
+/// Takes the log importance ratios and returns the normalized log weights along with the estimated
+/// Pareto shape k̂. Values of k̂ above 0.7 indicate that the estimate is unreliable. A NaN or
+/// infinite log ratio, e.g. from a draw with a log likelihood of -inf, gives a k̂ of infinity
+/// without any smoothing.
+pub fn psis(log_ratios: &[f64]) -> (Vec<f64>, f64) {
+    let s = log_ratios.len();
+    let max = log_ratios.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+    let mut lw = log_ratios.iter().map(|x| x - max).collect::<Vec<_>>();
+
+    let tail_len = f64::min(0.2 * s as f64, 3. * (s as f64).sqrt()).ceil() as usize;
+    let mut k = f64::INFINITY;
+
+    let finite = log_ratios.iter().all(|x| x.is_finite());
+
+    if finite && tail_len >= 5 && tail_len < s {
+        let mut order = (0..s).collect::<Vec<_>>();
+        order.sort_by(|&a, &b| lw[a].total_cmp(&lw[b]));
+        let tail = &order[s - tail_len..];
+        let cutoff = lw[order[s - tail_len - 1]];
+
+        if lw[tail[tail_len - 1]] - lw[tail[0]] > f64::EPSILON / 100. {
+            let exceedances = tail
+                .iter()
+                .map(|&i| lw[i].exp() - cutoff.exp())
+                .collect::<Vec<_>>();
+            let (khat, sigma) = gpd_fit(&exceedances);
+            k = khat;
+
+            if k.is_finite() {
+                for (z, &i) in tail.iter().enumerate() {
+                    let p = (z as f64 + 0.5) / tail_len as f64;
+                    let q = sigma * (-k * (-p).ln_1p()).exp_m1() / k;
+                    lw[i] = (q + cutoff.exp()).ln();
+                }
+            }
+        }
+    }
+
+    // truncate at the largest raw weight and normalize
+    lw.iter_mut().for_each(|x| *x = x.min(0.));
+    let lse = log_sum_exp(&lw);
+    lw.iter_mut().for_each(|x| *x -= lse);
+
+    (lw, k)
+}
+
+/// Calculates the Pareto smoothed importance sampling leave-one-out cross-validation (PSIS-LOO)
+/// estimate of the elpd from the pointwise log likelihood, with one row per observation and one
+/// column per draw (as returned by `pointwise_log_likelihood`).
+pub fn psis_loo(log_lik: &[Vec<f64>]) -> Elpd {
+    let mut pointwise = Vec::with_capacity(log_lik.len());
+    let mut p_pointwise = Vec::with_capacity(log_lik.len());
+    let mut pareto_k = Vec::with_capacity(log_lik.len());
+
+    for ll in log_lik {
+        let (lw, k) = psis(&ll.iter().map(|x| -x).collect::<Vec<_>>());
+        let elpd = log_sum_exp(&ll.iter().zip(&lw).map(|(l, w)| l + w).collect::<Vec<_>>());
+        let lppd = log_sum_exp(ll) - (ll.len() as f64).ln();
+        pointwise.push(elpd);
+        p_pointwise.push(lppd - elpd);
+        pareto_k.push(k);
+    }
+
+    Elpd {
+        pointwise,
+        p_pointwise,
+        pareto_k: Some(pareto_k),
+    }
+}
+
+/// One model in a `Comparison`.
+#[derive(Debug, Clone)]
+pub struct ComparisonRow {
+    pub name: String,
+    pub elpd: f64,
+    pub se: f64,
+    /// The difference in elpd from the best model.
+    pub elpd_diff: f64,
+    /// The standard error of the difference in elpd from the best model.
+    pub se_diff: f64,
+    pub p: f64,
+}
+
+/// A table of models ordered from best to worst by elpd.
+#[derive(Debug, Clone)]
+pub struct Comparison {
+    pub rows: Vec<ComparisonRow>,
+}
+
+impl std::fmt::Display for Comparison {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let width = self
+            .rows
+            .iter()
+            .map(|r| r.name.len())
+            .max()
+            .unwrap_or(0)
+            .max(5);
+        writeln!(
+            f,
+            "{:<width$} {:>10} {:>8} {:>10} {:>8} {:>8}",
+            "model",
+            "elpd",
+            "se",
+            "elpd_diff",
+            "se_diff",
+            "p",
+            width = width
+        )?;
+        for r in &self.rows {
+            writeln!(
+                f,
+                "{:<width$} {:>10.1} {:>8.1} {:>10.1} {:>8.1} {:>8.1}",
+                r.name,
+                r.elpd,
+                r.se,
+                r.elpd_diff,
+                r.se_diff,
+                r.p,
+                width = width
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Compares models fit to the same observations by their elpd (from `psis_loo` or `waic`). The
+/// standard errors of the differences are computed from the pointwise differences, which is much
+/// more accurate than combining the standard errors of each model. Models with a NaN elpd are
+/// ranked last.
+pub fn compare(models: &[(&str, &Elpd)]) -> Comparison {
+    assert!(!models.is_empty(), "There must be at least one model.");
+    let n = models[0].1.pointwise.len();
+    assert!(
+        models.iter().all(|(_, m)| m.pointwise.len() == n),
+        "All models must have the same number of observations."
+    );
+
+    let mut order = (0..models.len()).collect::<Vec<_>>();
+    let elpd = |i: usize| {
+        let elpd = models[i].1.elpd();
+        if elpd.is_nan() {
+            f64::NEG_INFINITY
+        } else {
+            elpd
+        }
+    };
+    order.sort_by(|&a, &b| elpd(b).total_cmp(&elpd(a)));
+    let best = models[order[0]].1;
+
+    let rows = order
+        .into_iter()
+        .map(|i| {
+            let (name, m) = models[i];
+            let diff = m
+                .pointwise
+                .iter()
+                .zip(&best.pointwise)
+                .map(|(a, b)| a - b)
+                .collect::<Vec<_>>();
+            ComparisonRow {
+                name: name.to_string(),
+                elpd: m.elpd(),
+                se: m.se(),
+                elpd_diff: diff.iter().sum(),
+                se_diff: if n > 1 {
+                    (n as f64 * var(&diff)).sqrt()
+                } else {
+                    0.
+                },
+                p: m.p(),
+            }
+        })
+        .collect();
+
+    Comparison { rows }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::distributions::{Distribution, Normal};
+    use crate::functions::inv_phi;
+
+    /// The values of `quantile` at `n` evenly spaced probabilities, standing in for a sample.
+    fn quantiles(n: usize, quantile: impl Fn(f64) -> f64) -> Vec<f64> {
+        (0..n)
+            .map(|i| quantile((i as f64 + 0.5) / n as f64))
+            .collect()
+    }
+
+    #[test]
+    fn psis_known_k() {
+        // log ratios whose exponentials follow a generalized Pareto distribution with shape k
+        for k in [0.2, 0.5, 0.9] {
+            let log_ratios = quantiles(10000, |p| ((1. - p).powf(-k) - 1.).ln() - k.ln());
+            let (lw, khat) = psis(&log_ratios);
+            assert!((khat - k).abs() < 0.05, "{} != {}", khat, k);
+            assert!(log_sum_exp(&lw).abs() < 1e-12);
+        }
+
+        let (_, khat) = psis(&quantiles(1000, |p| inv_phi(p)));
+        assert!(khat < 0.5);
+    }
+
+    #[test]
+    fn psis_non_finite_ratios() {
+        let mut log_ratios = quantiles(1000, |p| inv_phi(p));
+        log_ratios[10] = f64::NAN;
+        assert_eq!(psis(&log_ratios).1, f64::INFINITY);
+        log_ratios[10] = f64::INFINITY;
+        assert_eq!(psis(&log_ratios).1, f64::INFINITY);
+    }
+
+    #[test]
+    fn loo_matches_exact_loo() {
+        // y ~ Normal(mu, 1) with mu ~ Normal(0, 10), whose leave-one-out predictive densities are
+        // normal
+        let y = [
+            -0.63, 0.18, -0.84, 1.6, 0.33, -0.82, 0.49, 0.74, 0.58, -0.31, 1.51, 0.39, -0.62,
+            -2.21, 1.12, -0.04, -0.02, 0.94, 0.82, 3.5,
+        ];
+        let n = y.len() as f64;
+        let precision = 0.01 + n;
+        let (m, sd) = (y.iter().sum::<f64>() / precision, precision.recip().sqrt());
+        let mu = quantiles(4000, |p| m + sd * inv_phi(p));
+
+        let log_lik = y
+            .iter()
+            .map(|&yi| {
+                mu.iter()
+                    .map(|&mu| Normal::new(mu, 1.).log_density(yi))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let loo = psis_loo(&log_lik);
+
+        let sum = y.iter().sum::<f64>();
+        for (i, &yi) in y.iter().enumerate() {
+            let precision = 0.01 + n - 1.;
+            let predictive = Normal::new((sum - yi) / precision, (1. + 1. / precision).sqrt());
+            let exact = predictive.log_density(yi);
+            assert!(
+                (loo.pointwise[i] - exact).abs() < 2e-3,
+                "{} != {}",
+                loo.pointwise[i],
+                exact
+            );
+        }
+        assert!(loo.bad_k(0.7).is_empty());
+        assert!(loo.p() > 0.9 && loo.p() < 1.5);
+    }
+
+    #[test]
+    fn elpd_edge_cases() {
+        let single = Elpd {
+            pointwise: vec![-1.5],
+            p_pointwise: vec![0.5],
+            pareto_k: None,
+        };
+        assert_eq!(single.se(), 0.);
+
+        let nan = Elpd {
+            pointwise: vec![f64::NAN, -1.],
+            p_pointwise: vec![0.5, 0.5],
+            pareto_k: None,
+        };
+        let good = Elpd {
+            pointwise: vec![-2., -1.],
+            p_pointwise: vec![0.5, 0.5],
+            pareto_k: None,
+        };
+        let comparison = compare(&[("nan", &nan), ("good", &good)]);
+        assert_eq!(comparison.rows[0].name, "good");
+        assert_eq!(comparison.rows[1].name, "nan");
+    }
+}
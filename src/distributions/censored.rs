@@ -0,0 +1,69 @@
+//! Censored observations.
+
+use super::truncated::log_mass;
+use super::{Cdf, Variate};
+use crate::functions::Real;
+
+/// How an observation of a univariate distribution is censored, i.e. only known to be within an
+/// interval. Bounds are inclusive, which only matters for discrete distributions.
+#[derive(Debug, Clone, Copy)]
+pub enum Censoring<S> {
+    /// The observation is at most the given value, e.g. below a detection limit.
+    Left(S),
+    /// The observation is at least the given value, e.g. a survival time at the end of a study.
+    Right(S),
+    /// The observation is between the given values.
+    Interval(S, S),
+}
+
+impl<S: Variate> Censoring<S> {
+    /// Calculates the log probability of an observation of `dist` being censored like this, which
+    /// is its contribution to the log likelihood.
+    pub fn log_probability<T: Real, D: Cdf<T, Support = S>>(self, dist: &D) -> T {
+        let (lower, upper) = match self {
+            Censoring::Left(u) => (None, Some(u)),
+            Censoring::Right(l) => (Some(l), None),
+            Censoring::Interval(l, u) => (Some(l), Some(u)),
+        };
+        log_mass(dist, lower, upper).expect("A censored observation has at least one bound.")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::distributions::{assert_close, Exponential, Normal, Poisson};
+
+    #[test]
+    fn normal_mass() {
+        let d = Normal::new(0.5, 2.);
+        assert_close(
+            Censoring::Left(-1.).log_probability(&d),
+            -1.484448229919656292,
+        );
+        assert_close(
+            Censoring::Right(-1.).log_probability(&d),
+            -0.25699426683836523669,
+        );
+        assert_close(
+            Censoring::Interval(-1., 1.).log_probability(&d),
+            -0.98864915340665501009,
+        );
+        assert_close(
+            Censoring::Left(-40.).log_probability(&Normal::new(0., 1.)),
+            -804.60844201375378817,
+        );
+    }
+
+    #[test]
+    fn limits_below_the_support() {
+        let d = Exponential::new(1.5);
+        assert_eq!(Censoring::Left(-1.).log_probability(&d), f64::NEG_INFINITY);
+        assert_eq!(Censoring::Right(-1.).log_probability(&d), 0.);
+        assert_close(Censoring::Interval(-1., 1.).log_probability(&d), d.lcdf(1.));
+
+        let d = Poisson::new(2.5);
+        assert_eq!(Censoring::Left(-1).log_probability(&d), f64::NEG_INFINITY);
+        assert_eq!(Censoring::Right(0).log_probability(&d), 0.);
+    }
+}
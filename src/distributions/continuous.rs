@@ -0,0 +1,1085 @@
+//! Continuous distributions.
+
+use super::{random, Cdf, Distribution};
+use crate::functions::{self, Real};
+use std::f64::consts::{LN_2, PI, SQRT_2};
+
+/// The [normal distribution](https://en.wikipedia.org/wiki/Normal_distribution) with mean `mu`
+/// and standard deviation `sigma`.
+#[derive(Debug, Clone, Copy)]
+pub struct Normal<T> {
+    pub mu: T,
+    pub sigma: T,
+}
+
+impl<T: Real> Normal<T> {
+    pub fn new(mu: T, sigma: T) -> Self {
+        Self { mu, sigma }
+    }
+}
+
+impl<T: Real> Distribution<T> for Normal<T> {
+    type Support = T;
+
+    fn log_density(&self, x: T) -> T {
+        self.unnormalized_log_density(x) - 0.5 * (2. * PI).ln()
+    }
+
+    fn unnormalized_log_density(&self, x: T) -> T {
+        if self.sigma <= 0. {
+            return x.constant(f64::NEG_INFINITY);
+        }
+        -self.sigma.ln() - ((x - self.mu) / self.sigma).powi(2) * 0.5
+    }
+
+    fn sample(&self) -> f64 {
+        self.mu.value() + self.sigma.value() * random::standard_normal()
+    }
+}
+
+impl<T: Real + functions::Erf> Cdf<T> for Normal<T> {
+    fn cdf(&self, x: T) -> T {
+        functions::phi((x - self.mu) / self.sigma)
+    }
+
+    fn lcdf(&self, x: T) -> T {
+        functions::log_phi((x - self.mu) / self.sigma)
+    }
+
+    fn lccdf(&self, x: T) -> T {
+        functions::log_phi((self.mu - x) / self.sigma)
+    }
+}
+
+/// The [exponential distribution](https://en.wikipedia.org/wiki/Exponential_distribution) with
+/// rate `rate`.
+#[derive(Debug, Clone, Copy)]
+pub struct Exponential<T> {
+    pub rate: T,
+}
+
+impl<T: Real> Exponential<T> {
+    pub fn new(rate: T) -> Self {
+        Self { rate }
+    }
+}
+
+impl<T: Real> Distribution<T> for Exponential<T> {
+    type Support = T;
+
+    fn log_density(&self, x: T) -> T {
+        if self.rate <= 0. || x < 0. {
+            return x.constant(f64::NEG_INFINITY);
+        }
+        self.rate.ln() - self.rate * x
+    }
+
+    fn sample(&self) -> f64 {
+        random::standard_exponential() / self.rate.value()
+    }
+}
+
+impl<T: Real> Cdf<T> for Exponential<T> {
+    fn cdf(&self, x: T) -> T {
+        if x <= 0. {
+            return x.constant(0.);
+        }
+        -(-self.rate * x).exp() + 1.
+    }
+
+    fn lccdf(&self, x: T) -> T {
+        if x <= 0. {
+            return x.constant(0.);
+        }
+        -self.rate * x
+    }
+}
+
+/// The [continuous uniform
+/// distribution](https://en.wikipedia.org/wiki/Continuous_uniform_distribution) on the interval
+/// [`lower`, `upper`].
+#[derive(Debug, Clone, Copy)]
+pub struct Uniform<T> {
+    pub lower: T,
+    pub upper: T,
+}
+
+impl<T: Real> Uniform<T> {
+    pub fn new(lower: T, upper: T) -> Self {
+        Self { lower, upper }
+    }
+}
+
+impl<T: Real> Distribution<T> for Uniform<T> {
+    type Support = T;
+
+    fn log_density(&self, x: T) -> T {
+        let (lower, upper) = (self.lower.value(), self.upper.value());
+        if lower < upper && lower <= x.value() && x.value() <= upper {
+            -(self.upper - self.lower).ln()
+        } else {
+            x.constant(f64::NEG_INFINITY)
+        }
+    }
+
+    fn sample(&self) -> f64 {
+        let (lower, upper) = (self.lower.value(), self.upper.value());
+        lower + (upper - lower) * alea::f64()
+    }
+}
+
+impl<T: Real> Cdf<T> for Uniform<T> {
+    fn cdf(&self, x: T) -> T {
+        if x.value() < self.lower.value() {
+            x.constant(0.)
+        } else if x.value() > self.upper.value() {
+            x.constant(1.)
+        } else {
+            (x - self.lower) / (self.upper - self.lower)
+        }
+    }
+}
+
+/// The [gamma distribution](https://en.wikipedia.org/wiki/Gamma_distribution) with shape `alpha`
+/// and rate `beta`.
+#[derive(Debug, Clone, Copy)]
+pub struct Gamma<T> {
+    pub alpha: T,
+    pub beta: T,
+}
+
+impl<T: Real> Gamma<T> {
+    pub fn new(alpha: T, beta: T) -> Self {
+        Self { alpha, beta }
+    }
+}
+
+impl<T: Real + functions::Gamma> Distribution<T> for Gamma<T> {
+    type Support = T;
+
+    fn log_density(&self, x: T) -> T {
+        if x <= 0. || self.alpha <= 0. || self.beta <= 0. {
+            return x.constant(f64::NEG_INFINITY);
+        }
+        self.alpha * self.beta.ln() + (self.alpha - 1.) * x.ln()
+            - self.beta * x
+            - functions::lgamma(self.alpha)
+    }
+
+    fn sample(&self) -> f64 {
+        random::standard_gamma(self.alpha.value()) / self.beta.value()
+    }
+}
+
+impl<T: Real + functions::Gamma + functions::IncompleteGamma> Cdf<T> for Gamma<T> {
+    fn cdf(&self, x: T) -> T {
+        if x <= 0. {
+            return x.constant(0.);
+        }
+        functions::gamma_p(self.alpha, self.beta * x)
+    }
+
+    fn lccdf(&self, x: T) -> T {
+        if x <= 0. {
+            return x.constant(0.);
+        }
+        functions::gamma_q(self.alpha, self.beta * x).ln()
+    }
+}
+
+/// The [Laplace distribution](https://en.wikipedia.org/wiki/Laplace_distribution) with location
+/// `mu` and scale `b`.
+#[derive(Debug, Clone, Copy)]
+pub struct Laplace<T> {
+    pub mu: T,
+    pub b: T,
+}
+
+impl<T: Real> Laplace<T> {
+    pub fn new(mu: T, b: T) -> Self {
+        Self { mu, b }
+    }
+}
+
+impl<T: Real> Distribution<T> for Laplace<T> {
+    type Support = T;
+
+    fn log_density(&self, x: T) -> T {
+        self.unnormalized_log_density(x) - LN_2
+    }
+
+    fn unnormalized_log_density(&self, x: T) -> T {
+        if self.b <= 0. {
+            return x.constant(f64::NEG_INFINITY);
+        }
+        -self.b.ln() - (x - self.mu).abs() / self.b
+    }
+
+    fn sample(&self) -> f64 {
+        let u = random::uniform() - 0.5;
+        self.mu.value() - self.b.value() * u.signum() * (1. - 2. * u.abs()).ln()
+    }
+}
+
+impl<T: Real> Cdf<T> for Laplace<T> {
+    fn cdf(&self, x: T) -> T {
+        let z = (x - self.mu) / self.b;
+        if z < 0. {
+            z.exp() * 0.5
+        } else {
+            -(-z).exp() * 0.5 + 1.
+        }
+    }
+}
+
+/// The [beta distribution](https://en.wikipedia.org/wiki/Beta_distribution) with shape parameters
+/// `alpha` and `beta`.
+#[derive(Debug, Clone, Copy)]
+pub struct Beta<T> {
+    pub alpha: T,
+    pub beta: T,
+}
+
+impl<T: Real> Beta<T> {
+    pub fn new(alpha: T, beta: T) -> Self {
+        Self { alpha, beta }
+    }
+}
+
+impl<T: Real + functions::Gamma> Distribution<T> for Beta<T> {
+    type Support = T;
+
+    fn log_density(&self, x: T) -> T {
+        if x < 0. || x > 1. || self.alpha <= 0. || self.beta <= 0. {
+            return x.constant(f64::NEG_INFINITY);
+        }
+        // A power term with a zero exponent is 1 even where its base is 0, but 0 * -inf is NaN on
+        // the log scale, so it is left out at the boundaries.
+        let lower = if self.alpha.value() == 1. && x.value() == 0. {
+            x.constant(0.)
+        } else {
+            (self.alpha - 1.) * x.ln()
+        };
+        let upper = if self.beta.value() == 1. && x.value() == 1. {
+            x.constant(0.)
+        } else {
+            (self.beta - 1.) * (-x + 1.).ln()
+        };
+        lower + upper - functions::lbeta(self.alpha, self.beta)
+    }
+
+    fn sample(&self) -> f64 {
+        random::beta(self.alpha.value(), self.beta.value())
+    }
+}
+
+impl<T: Real + functions::Gamma + functions::IncompleteBeta> Cdf<T> for Beta<T> {
+    fn cdf(&self, x: T) -> T {
+        if x <= 0. {
+            x.constant(0.)
+        } else if x >= 1. {
+            x.constant(1.)
+        } else {
+            functions::beta_inc(self.alpha, self.beta, x)
+        }
+    }
+
+    fn lccdf(&self, x: T) -> T {
+        if x <= 0. {
+            x.constant(0.)
+        } else if x >= 1. {
+            x.constant(f64::NEG_INFINITY)
+        } else {
+            functions::beta_inc(self.beta, self.alpha, -x + 1.).ln()
+        }
+    }
+}
+
+/// The [Cauchy distribution](https://en.wikipedia.org/wiki/Cauchy_distribution) with location
+/// `mu` and scale `sigma`.
+#[derive(Debug, Clone, Copy)]
+pub struct Cauchy<T> {
+    pub mu: T,
+    pub sigma: T,
+}
+
+impl<T: Real> Cauchy<T> {
+    pub fn new(mu: T, sigma: T) -> Self {
+        Self { mu, sigma }
+    }
+}
+
+impl<T: Real> Distribution<T> for Cauchy<T> {
+    type Support = T;
+
+    fn log_density(&self, x: T) -> T {
+        self.unnormalized_log_density(x) - PI.ln()
+    }
+
+    fn unnormalized_log_density(&self, x: T) -> T {
+        if self.sigma <= 0. {
+            return x.constant(f64::NEG_INFINITY);
+        }
+        (self.sigma / (self.sigma.powi(2) + (self.mu - x).powi(2))).ln()
+    }
+
+    fn sample(&self) -> f64 {
+        self.mu.value() + self.sigma.value() * (PI * (random::uniform() - 0.5)).tan()
+    }
+}
+
+impl<T: Real> Cdf<T> for Cauchy<T> {
+    fn cdf(&self, x: T) -> T {
+        ((x - self.mu) / self.sigma).atan() / PI + 0.5
+    }
+}
+
+/// The [log-normal distribution](https://en.wikipedia.org/wiki/Log-normal_distribution), whose
+/// logarithm is normal with mean `mu` and standard deviation `sigma`.
+#[derive(Debug, Clone, Copy)]
+pub struct Lognormal<T> {
+    pub mu: T,
+    pub sigma: T,
+}
+
+impl<T: Real> Lognormal<T> {
+    pub fn new(mu: T, sigma: T) -> Self {
+        Self { mu, sigma }
+    }
+}
+
+impl<T: Real> Distribution<T> for Lognormal<T> {
+    type Support = T;
+
+    fn log_density(&self, x: T) -> T {
+        self.unnormalized_log_density(x) - 0.5 * (2. * PI).ln()
+    }
+
+    fn unnormalized_log_density(&self, x: T) -> T {
+        if self.sigma <= 0. || x <= 0. {
+            return x.constant(f64::NEG_INFINITY);
+        }
+        -((x.ln() - self.mu) / self.sigma).powi(2) * 0.5 - x.ln() - self.sigma.ln()
+    }
+
+    fn sample(&self) -> f64 {
+        (self.mu.value() + self.sigma.value() * random::standard_normal()).exp()
+    }
+}
+
+impl<T: Real + functions::Erf> Cdf<T> for Lognormal<T> {
+    fn cdf(&self, x: T) -> T {
+        functions::phi((x.ln() - self.mu) / self.sigma)
+    }
+
+    fn lcdf(&self, x: T) -> T {
+        functions::log_phi((x.ln() - self.mu) / self.sigma)
+    }
+
+    fn lccdf(&self, x: T) -> T {
+        functions::log_phi((self.mu - x.ln()) / self.sigma)
+    }
+}
+
+/// The [Rayleigh distribution](https://en.wikipedia.org/wiki/Rayleigh_distribution) with scale
+/// `sigma`.
+#[derive(Debug, Clone, Copy)]
+pub struct Rayleigh<T> {
+    pub sigma: T,
+}
+
+impl<T: Real> Rayleigh<T> {
+    pub fn new(sigma: T) -> Self {
+        Self { sigma }
+    }
+}
+
+impl<T: Real> Distribution<T> for Rayleigh<T> {
+    type Support = T;
+
+    fn log_density(&self, x: T) -> T {
+        if self.sigma <= 0. || x < 0. {
+            return x.constant(f64::NEG_INFINITY);
+        }
+        -x.powi(2) / (self.sigma.powi(2) * 2.) + x.ln() - self.sigma.ln() * 2.
+    }
+
+    fn sample(&self) -> f64 {
+        self.sigma.value() * (2. * random::standard_exponential()).sqrt()
+    }
+}
+
+impl<T: Real> Cdf<T> for Rayleigh<T> {
+    fn cdf(&self, x: T) -> T {
+        if x <= 0. {
+            return x.constant(0.);
+        }
+        -(-x.powi(2) / (self.sigma.powi(2) * 2.)).exp() + 1.
+    }
+
+    fn lccdf(&self, x: T) -> T {
+        if x <= 0. {
+            return x.constant(0.);
+        }
+        -x.powi(2) / (self.sigma.powi(2) * 2.)
+    }
+}
+
+/// The [Pareto distribution](https://en.wikipedia.org/wiki/Pareto_distribution) with minimum
+/// `ymin` and shape `alpha`.
+#[derive(Debug, Clone, Copy)]
+pub struct Pareto<T> {
+    pub ymin: T,
+    pub alpha: T,
+}
+
+impl<T: Real> Pareto<T> {
+    pub fn new(ymin: T, alpha: T) -> Self {
+        Self { ymin, alpha }
+    }
+}
+
+impl<T: Real> Distribution<T> for Pareto<T> {
+    type Support = T;
+
+    fn log_density(&self, x: T) -> T {
+        if self.ymin <= 0. || self.alpha <= 0. || x.value() < self.ymin.value() {
+            return x.constant(f64::NEG_INFINITY);
+        }
+        self.alpha.ln() + self.alpha * self.ymin.ln() - (self.alpha + 1.) * x.ln()
+    }
+
+    fn sample(&self) -> f64 {
+        self.ymin.value() * random::uniform().powf(-1. / self.alpha.value())
+    }
+}
+
+impl<T: Real> Cdf<T> for Pareto<T> {
+    fn cdf(&self, x: T) -> T {
+        if x.value() <= self.ymin.value() {
+            return x.constant(0.);
+        }
+        -self.lccdf(x).exp() + 1.
+    }
+
+    fn lccdf(&self, x: T) -> T {
+        if x.value() <= self.ymin.value() {
+            return x.constant(0.);
+        }
+        self.alpha * (self.ymin.ln() - x.ln())
+    }
+}
+
+/// The [Student's t-distribution](https://en.wikipedia.org/wiki/Student%27s_t-distribution) with
+/// `nu` degrees of freedom, location `mu` and scale `sigma`.
+#[derive(Debug, Clone, Copy)]
+pub struct StudentT<T> {
+    pub nu: T,
+    pub mu: T,
+    pub sigma: T,
+}
+
+impl<T: Real> StudentT<T> {
+    pub fn new(nu: T, mu: T, sigma: T) -> Self {
+        Self { nu, mu, sigma }
+    }
+}
+
+impl<T: Real + functions::Gamma> Distribution<T> for StudentT<T> {
+    type Support = T;
+
+    fn log_density(&self, x: T) -> T {
+        self.unnormalized_log_density(x) - 0.5 * PI.ln()
+    }
+
+    fn unnormalized_log_density(&self, x: T) -> T {
+        if self.nu <= 0. || self.sigma <= 0. {
+            return x.constant(f64::NEG_INFINITY);
+        }
+        let z = (x - self.mu) / self.sigma;
+        functions::lgamma((self.nu + 1.) * 0.5)
+            - functions::lgamma(self.nu * 0.5)
+            - self.nu.ln() * 0.5
+            - self.sigma.ln()
+            - (self.nu + 1.) * 0.5 * (z.powi(2) / self.nu + 1.).ln()
+    }
+
+    fn sample(&self) -> f64 {
+        let nu = self.nu.value();
+        let chi2 = 2. * random::standard_gamma(nu / 2.);
+        self.mu.value() + self.sigma.value() * random::standard_normal() / (chi2 / nu).sqrt()
+    }
+}
+
+impl<T: Real + functions::IncompleteBeta> StudentT<T> {
+    /// Calculates the probability of being further from `mu` than `x`, on the same side.
+    fn tail(&self, x: T) -> T {
+        let z = (x - self.mu) / self.sigma;
+        let ratio = self.nu / (z.powi(2) + self.nu);
+        functions::beta_inc(self.nu * 0.5, self.nu.constant(0.5), ratio) * 0.5
+    }
+}
+
+impl<T: Real + functions::Gamma + functions::IncompleteBeta> Cdf<T> for StudentT<T> {
+    fn cdf(&self, x: T) -> T {
+        let tail = self.tail(x);
+        if x.value() < self.mu.value() {
+            tail
+        } else {
+            -tail + 1.
+        }
+    }
+
+    fn lcdf(&self, x: T) -> T {
+        let tail = self.tail(x);
+        if x.value() < self.mu.value() {
+            tail.ln()
+        } else {
+            (-tail).ln_1p()
+        }
+    }
+
+    fn lccdf(&self, x: T) -> T {
+        let tail = self.tail(x);
+        if x.value() < self.mu.value() {
+            (-tail).ln_1p()
+        } else {
+            tail.ln()
+        }
+    }
+}
+
+/// The [half-normal distribution](https://en.wikipedia.org/wiki/Half-normal_distribution), which
+/// is a normal distribution with mean 0 and standard deviation `sigma` folded onto [0, inf).
+#[derive(Debug, Clone, Copy)]
+pub struct HalfNormal<T> {
+    pub sigma: T,
+}
+
+impl<T: Real> HalfNormal<T> {
+    pub fn new(sigma: T) -> Self {
+        Self { sigma }
+    }
+}
+
+impl<T: Real> Distribution<T> for HalfNormal<T> {
+    type Support = T;
+
+    fn log_density(&self, x: T) -> T {
+        self.unnormalized_log_density(x) + LN_2 - 0.5 * (2. * PI).ln()
+    }
+
+    fn unnormalized_log_density(&self, x: T) -> T {
+        if self.sigma <= 0. || x < 0. {
+            return x.constant(f64::NEG_INFINITY);
+        }
+        -self.sigma.ln() - (x / self.sigma).powi(2) * 0.5
+    }
+
+    fn sample(&self) -> f64 {
+        (self.sigma.value() * random::standard_normal()).abs()
+    }
+}
+
+impl<T: Real + functions::Erf> Cdf<T> for HalfNormal<T> {
+    fn cdf(&self, x: T) -> T {
+        if x < 0. {
+            return x.constant(0.);
+        }
+        functions::erf(x / (self.sigma * SQRT_2))
+    }
+
+    fn lccdf(&self, x: T) -> T {
+        if x < 0. {
+            return x.constant(0.);
+        }
+        functions::erfc(x / (self.sigma * SQRT_2)).ln()
+    }
+}
+
+/// The half-Cauchy distribution, which is a Cauchy distribution with location 0 and scale `sigma`
+/// folded onto [0, inf).
+#[derive(Debug, Clone, Copy)]
+pub struct HalfCauchy<T> {
+    pub sigma: T,
+}
+
+impl<T: Real> HalfCauchy<T> {
+    pub fn new(sigma: T) -> Self {
+        Self { sigma }
+    }
+}
+
+impl<T: Real> Distribution<T> for HalfCauchy<T> {
+    type Support = T;
+
+    fn log_density(&self, x: T) -> T {
+        self.unnormalized_log_density(x) + LN_2 - PI.ln()
+    }
+
+    fn unnormalized_log_density(&self, x: T) -> T {
+        if self.sigma <= 0. || x < 0. {
+            return x.constant(f64::NEG_INFINITY);
+        }
+        (self.sigma / (self.sigma.powi(2) + x.powi(2))).ln()
+    }
+
+    fn sample(&self) -> f64 {
+        (self.sigma.value() * (PI * (random::uniform() - 0.5)).tan()).abs()
+    }
+}
+
+impl<T: Real> Cdf<T> for HalfCauchy<T> {
+    fn cdf(&self, x: T) -> T {
+        if x < 0. {
+            return x.constant(0.);
+        }
+        (x / self.sigma).atan() * (2. / PI)
+    }
+}
+
+/// The half-Student-t distribution, which is a Student's t-distribution with `nu` degrees of
+/// freedom, location 0 and scale `sigma` folded onto [0, inf).
+#[derive(Debug, Clone, Copy)]
+pub struct HalfStudentT<T> {
+    pub nu: T,
+    pub sigma: T,
+}
+
+impl<T: Real> HalfStudentT<T> {
+    pub fn new(nu: T, sigma: T) -> Self {
+        Self { nu, sigma }
+    }
+}
+
+impl<T: Real + functions::Gamma> Distribution<T> for HalfStudentT<T> {
+    type Support = T;
+
+    fn log_density(&self, x: T) -> T {
+        self.unnormalized_log_density(x) + LN_2 - 0.5 * PI.ln()
+    }
+
+    fn unnormalized_log_density(&self, x: T) -> T {
+        if x < 0. {
+            return x.constant(f64::NEG_INFINITY);
+        }
+        StudentT::new(self.nu, x.constant(0.), self.sigma).unnormalized_log_density(x)
+    }
+
+    fn sample(&self) -> f64 {
+        let nu = self.nu.value();
+        let chi2 = 2. * random::standard_gamma(nu / 2.);
+        (self.sigma.value() * random::standard_normal() / (chi2 / nu).sqrt()).abs()
+    }
+}
+
+/// The [inverse-gamma distribution](https://en.wikipedia.org/wiki/Inverse-gamma_distribution) with
+/// shape `alpha` and scale `beta`.
+#[derive(Debug, Clone, Copy)]
+pub struct InverseGamma<T> {
+    pub alpha: T,
+    pub beta: T,
+}
+
+impl<T: Real> InverseGamma<T> {
+    pub fn new(alpha: T, beta: T) -> Self {
+        Self { alpha, beta }
+    }
+}
+
+impl<T: Real + functions::Gamma> Distribution<T> for InverseGamma<T> {
+    type Support = T;
+
+    fn log_density(&self, x: T) -> T {
+        if x <= 0. || self.alpha <= 0. || self.beta <= 0. {
+            return x.constant(f64::NEG_INFINITY);
+        }
+        self.alpha * self.beta.ln()
+            - functions::lgamma(self.alpha)
+            - (self.alpha + 1.) * x.ln()
+            - self.beta / x
+    }
+
+    fn sample(&self) -> f64 {
+        self.beta.value() / random::standard_gamma(self.alpha.value())
+    }
+}
+
+/// The [Weibull distribution](https://en.wikipedia.org/wiki/Weibull_distribution) with shape `k`
+/// and scale `lambda`.
+#[derive(Debug, Clone, Copy)]
+pub struct Weibull<T> {
+    pub k: T,
+    pub lambda: T,
+}
+
+impl<T: Real> Weibull<T> {
+    pub fn new(k: T, lambda: T) -> Self {
+        Self { k, lambda }
+    }
+}
+
+impl<T: Real> Distribution<T> for Weibull<T> {
+    type Support = T;
+
+    fn log_density(&self, x: T) -> T {
+        if x <= 0. || self.k <= 0. || self.lambda <= 0. {
+            return x.constant(f64::NEG_INFINITY);
+        }
+        let log_z = x.ln() - self.lambda.ln();
+        self.k.ln() - self.lambda.ln() + (self.k - 1.) * log_z - (self.k * log_z).exp()
+    }
+
+    fn sample(&self) -> f64 {
+        self.lambda.value() * random::standard_exponential().powf(1. / self.k.value())
+    }
+}
+
+impl<T: Real> Cdf<T> for Weibull<T> {
+    fn cdf(&self, x: T) -> T {
+        if x <= 0. {
+            return x.constant(0.);
+        }
+        -self.lccdf(x).exp() + 1.
+    }
+
+    fn lccdf(&self, x: T) -> T {
+        if x <= 0. {
+            return x.constant(0.);
+        }
+        -(self.k * (x.ln() - self.lambda.ln())).exp()
+    }
+}
+
+/// The [Gumbel distribution](https://en.wikipedia.org/wiki/Gumbel_distribution) with location
+/// `mu` and scale `beta`.
+#[derive(Debug, Clone, Copy)]
+pub struct Gumbel<T> {
+    pub mu: T,
+    pub beta: T,
+}
+
+impl<T: Real> Gumbel<T> {
+    pub fn new(mu: T, beta: T) -> Self {
+        Self { mu, beta }
+    }
+}
+
+impl<T: Real> Distribution<T> for Gumbel<T> {
+    type Support = T;
+
+    fn log_density(&self, x: T) -> T {
+        if self.beta <= 0. {
+            return x.constant(f64::NEG_INFINITY);
+        }
+        let z = (x - self.mu) / self.beta;
+        -self.beta.ln() - z - (-z).exp()
+    }
+
+    fn sample(&self) -> f64 {
+        self.mu.value() - self.beta.value() * random::standard_exponential().ln()
+    }
+}
+
+impl<T: Real> Cdf<T> for Gumbel<T> {
+    fn cdf(&self, x: T) -> T {
+        self.lcdf(x).exp()
+    }
+
+    fn lcdf(&self, x: T) -> T {
+        -(-(x - self.mu) / self.beta).exp()
+    }
+}
+
+/// The [logistic distribution](https://en.wikipedia.org/wiki/Logistic_distribution) with location
+/// `mu` and scale `s`.
+#[derive(Debug, Clone, Copy)]
+pub struct Logistic<T> {
+    pub mu: T,
+    pub s: T,
+}
+
+impl<T: Real> Logistic<T> {
+    pub fn new(mu: T, s: T) -> Self {
+        Self { mu, s }
+    }
+}
+
+impl<T: Real> Distribution<T> for Logistic<T> {
+    type Support = T;
+
+    fn log_density(&self, x: T) -> T {
+        if self.s <= 0. {
+            return x.constant(f64::NEG_INFINITY);
+        }
+        // the density is symmetric, so use |z| to avoid overflow
+        let z = ((x - self.mu) / self.s).abs();
+        -self.s.ln() - z - functions::log1p_exp(-z) * 2.
+    }
+
+    fn sample(&self) -> f64 {
+        let u = random::uniform();
+        self.mu.value() + self.s.value() * (u / (1. - u)).ln()
+    }
+}
+
+impl<T: Real> Cdf<T> for Logistic<T> {
+    fn cdf(&self, x: T) -> T {
+        ((-(x - self.mu) / self.s).exp() + 1.).recip()
+    }
+}
+
+/// The [skew normal distribution](https://en.wikipedia.org/wiki/Skew_normal_distribution) with
+/// location `xi`, scale `omega` and shape `alpha`.
+#[derive(Debug, Clone, Copy)]
+pub struct SkewNormal<T> {
+    pub xi: T,
+    pub omega: T,
+    pub alpha: T,
+}
+
+impl<T: Real> SkewNormal<T> {
+    pub fn new(xi: T, omega: T, alpha: T) -> Self {
+        Self { xi, omega, alpha }
+    }
+}
+
+impl<T: Real + functions::Erf> Distribution<T> for SkewNormal<T> {
+    type Support = T;
+
+    fn log_density(&self, x: T) -> T {
+        self.unnormalized_log_density(x) + LN_2 - 0.5 * (2. * PI).ln()
+    }
+
+    fn unnormalized_log_density(&self, x: T) -> T {
+        if self.omega <= 0. {
+            return x.constant(f64::NEG_INFINITY);
+        }
+        let z = (x - self.xi) / self.omega;
+        -self.omega.ln() - z.powi(2) * 0.5 + functions::log_phi(self.alpha * z)
+    }
+
+    fn sample(&self) -> f64 {
+        let alpha = self.alpha.value();
+        let delta = alpha / (1. + alpha * alpha).sqrt();
+        let u0 = random::standard_normal();
+        let u1 = delta * u0 + (1. - delta * delta).sqrt() * random::standard_normal();
+        let z = if u0 >= 0. { u1 } else { -u1 };
+        self.xi.value() + self.omega.value() * z
+    }
+}
+
+/// The [exponentially modified normal
+/// distribution](https://en.wikipedia.org/wiki/Exponentially_modified_Gaussian_distribution),
+/// which is the sum of a normal with mean `mu` and standard deviation `sigma` and an exponential
+/// with rate `lambda`.
+#[derive(Debug, Clone, Copy)]
+pub struct ExpModNormal<T> {
+    pub mu: T,
+    pub sigma: T,
+    pub lambda: T,
+}
+
+impl<T: Real> ExpModNormal<T> {
+    pub fn new(mu: T, sigma: T, lambda: T) -> Self {
+        Self { mu, sigma, lambda }
+    }
+}
+
+impl<T: Real + functions::Erf> Distribution<T> for ExpModNormal<T> {
+    type Support = T;
+
+    fn log_density(&self, x: T) -> T {
+        if self.sigma <= 0. || self.lambda <= 0. {
+            return x.constant(f64::NEG_INFINITY);
+        }
+        let s2 = self.sigma.powi(2);
+        self.lambda.ln()
+            + self.lambda * (self.mu * 2. + self.lambda * s2 - x * 2.) * 0.5
+            + functions::log_phi((x - self.mu - self.lambda * s2) / self.sigma)
+    }
+
+    fn sample(&self) -> f64 {
+        self.mu.value()
+            + self.sigma.value() * random::standard_normal()
+            + random::standard_exponential() / self.lambda.value()
+    }
+}
+
+impl<T: Real + functions::Erf> Cdf<T> for ExpModNormal<T> {
+    fn cdf(&self, x: T) -> T {
+        let u = (x - self.mu) / self.sigma;
+        let v = self.lambda * self.sigma;
+        // the exponential may overflow where Phi(u - v) underflows, so they are combined in logs
+        let correction = -self.lambda * (x - self.mu) + v.powi(2) * 0.5 + functions::log_phi(u - v);
+        functions::phi(u) - correction.exp()
+    }
+}
+
+/// Wraps an angle onto [-pi, pi).
+fn wrap_angle(x: f64) -> f64 {
+    x - 2. * PI * ((x + PI) / (2. * PI)).floor()
+}
+
+/// The [von Mises distribution](https://en.wikipedia.org/wiki/Von_Mises_distribution) of angles,
+/// with mean direction `mu` and concentration `kappa`.
+///
+/// The log density is periodic, so angles may be given on any interval of length 2 pi. Samples
+/// are on [-pi, pi).
+#[derive(Debug, Clone, Copy)]
+pub struct VonMises<T> {
+    pub mu: T,
+    pub kappa: T,
+}
+
+impl<T: Real> VonMises<T> {
+    pub fn new(mu: T, kappa: T) -> Self {
+        Self { mu, kappa }
+    }
+}
+
+impl<T: Real + functions::Bessel> Distribution<T> for VonMises<T> {
+    type Support = T;
+
+    fn log_density(&self, x: T) -> T {
+        self.unnormalized_log_density(x) - (2. * PI).ln()
+    }
+
+    fn unnormalized_log_density(&self, x: T) -> T {
+        if self.kappa < 0. {
+            return x.constant(f64::NEG_INFINITY);
+        }
+        self.kappa * (x - self.mu).cos() - functions::log_bessel_i(0., self.kappa)
+    }
+
+    /// Draws a random sample with the algorithm of Best and Fisher (1979).
+    fn sample(&self) -> f64 {
+        let (mu, kappa) = (self.mu.value(), self.kappa.value());
+        if kappa < 1e-8 {
+            return wrap_angle(mu + 2. * PI * alea::f64());
+        }
+        let tau = 1. + (1. + 4. * kappa * kappa).sqrt();
+        let rho = (tau - (2. * tau).sqrt()) / (2. * kappa);
+        let r = (1. + rho * rho) / (2. * rho);
+        loop {
+            let z = (PI * alea::f64()).cos();
+            let f = (1. + r * z) / (r + z);
+            let c = kappa * (r - f);
+            let u = random::uniform();
+            if c * (2. - c) > u || (c / u).ln() + 1. >= c {
+                let theta = if alea::f64() < 0.5 {
+                    -f.acos()
+                } else {
+                    f.acos()
+                };
+                return wrap_angle(mu + theta);
+            }
+        }
+    }
+}
+
+/// The [wrapped normal distribution](https://en.wikipedia.org/wiki/Wrapped_normal_distribution)
+/// of angles, which is a normal with mean `mu` and standard deviation `sigma` wrapped around the
+/// circle.
+///
+/// The log density is periodic, so angles may be given on any interval of length 2 pi. Samples
+/// are on [-pi, pi).
+#[derive(Debug, Clone, Copy)]
+pub struct WrappedNormal<T> {
+    pub mu: T,
+    pub sigma: T,
+}
+
+impl<T: Real> WrappedNormal<T> {
+    pub fn new(mu: T, sigma: T) -> Self {
+        Self { mu, sigma }
+    }
+}
+
+impl<T: Real> Distribution<T> for WrappedNormal<T> {
+    type Support = T;
+
+    fn log_density(&self, x: T) -> T {
+        if self.sigma <= 0. {
+            return x.constant(f64::NEG_INFINITY);
+        }
+        let d = x - self.mu;
+        let d = d - (d.value() - wrap_angle(d.value()));
+        let sigma = self.sigma.value();
+        if sigma < 2. {
+            // sum over the windings within 9 standard deviations
+            let windings = ((9. * sigma + PI) / (2. * PI)).ceil() as i64;
+            let terms = (-windings..=windings)
+                .map(|k| -((d + 2. * PI * k as f64) / self.sigma).powi(2) * 0.5)
+                .collect::<Vec<_>>();
+            functions::log_sum_exp(&terms) - self.sigma.ln() - 0.5 * (2. * PI).ln()
+        } else {
+            // for wide distributions the Fourier series converges much faster
+            let harmonics = (9. / sigma).ceil() as i64;
+            let series = (1..=harmonics).fold(d.constant(1.), |acc, n| {
+                let n = n as f64;
+                acc + (self.sigma.powi(2) * (-0.5 * n * n)).exp() * (d * n).cos() * 2.
+            });
+            series.ln() - (2. * PI).ln()
+        }
+    }
+
+    fn sample(&self) -> f64 {
+        wrap_angle(self.mu.value() + self.sigma.value() * random::standard_normal())
+    }
+}
+
+/// The [wrapped Cauchy distribution](https://en.wikipedia.org/wiki/Wrapped_Cauchy_distribution)
+/// of angles, which is a Cauchy with location `mu` and scale `sigma` wrapped around the circle.
+///
+/// The log density is periodic, so angles may be given on any interval of length 2 pi. Samples
+/// are on [-pi, pi).
+#[derive(Debug, Clone, Copy)]
+pub struct WrappedCauchy<T> {
+    pub mu: T,
+    pub sigma: T,
+}
+
+impl<T: Real> WrappedCauchy<T> {
+    pub fn new(mu: T, sigma: T) -> Self {
+        Self { mu, sigma }
+    }
+}
+
+impl<T: Real> Distribution<T> for WrappedCauchy<T> {
+    type Support = T;
+
+    fn log_density(&self, x: T) -> T {
+        self.unnormalized_log_density(x) - (2. * PI).ln()
+    }
+
+    fn unnormalized_log_density(&self, x: T) -> T {
+        if self.sigma <= 0. {
+            return x.constant(f64::NEG_INFINITY);
+        }
+        let rho = (-self.sigma).exp();
+        functions::log1m_exp(-self.sigma * 2.)
+            - (rho.powi(2) + 1. - rho * (x - self.mu).cos() * 2.).ln()
+    }
+
+    fn sample(&self) -> f64 {
+        let z = (PI * (random::uniform() - 0.5)).tan();
+        wrap_angle(self.mu.value() + self.sigma.value() * z)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::distributions::assert_close;
+
+    #[test]
+    fn beta_boundaries() {
+        assert_close(Beta::new(1., 3.).log_density(0.), 3_f64.ln());
+        assert_close(Beta::new(2., 1.).log_density(1.), 2_f64.ln());
+        assert_close(Beta::new(1., 1.).log_density(0.), 0.);
+        assert_close(Beta::new(1., 1.).log_density(1.), 0.);
+        assert_eq!(Beta::new(2., 3.).log_density(0.), f64::NEG_INFINITY);
+        assert_eq!(Beta::new(0.5, 3.).log_density(0.), f64::INFINITY);
+    }
+}
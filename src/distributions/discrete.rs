@@ -0,0 +1,861 @@
+//! Discrete distributions.
+
+use super::{is_simplex, random, Cdf, Distribution};
+use crate::functions::{self, Real};
+
+/// Calculates ln(n!).
+pub(super) fn ln_factorial(n: i64) -> f64 {
+    functions::lfactorial(n as f64)
+}
+
+/// Calculates ln(x (x + 1) ... (x + k - 1)) = ln(Γ(x + k) / Γ(x)), the log of the rising
+/// factorial.
+fn ln_rising<T: Real + functions::Gamma>(x: T, k: i64) -> T {
+    functions::lgamma(x + k as f64) - functions::lgamma(x)
+}
+
+/// Sums the probability masses from 0 up to `k`.
+fn sum_masses<T: Real, D: Distribution<T, Support = i64>>(d: &D, k: i64) -> T {
+    (1..=k).fold(d.log_density(0).exp(), |acc, i| {
+        acc + d.log_density(i).exp()
+    })
+}
+
+/// The [Bernoulli distribution](https://en.wikipedia.org/wiki/Bernoulli_distribution) with
+/// success probability `theta`.
+#[derive(Debug, Clone, Copy)]
+pub struct Bernoulli<T> {
+    pub theta: T,
+}
+
+impl<T: Real> Bernoulli<T> {
+    pub fn new(theta: T) -> Self {
+        Self { theta }
+    }
+}
+
+impl<T: Real> Distribution<T> for Bernoulli<T> {
+    type Support = i64;
+
+    fn log_density(&self, k: i64) -> T {
+        if self.theta < 0. || self.theta > 1. {
+            return self.theta.constant(f64::NEG_INFINITY);
+        }
+        match k {
+            0 => (-self.theta + 1.).ln(),
+            1 => self.theta.ln(),
+            _ => self.theta.constant(f64::NEG_INFINITY),
+        }
+    }
+
+    fn sample(&self) -> f64 {
+        if alea::f64() < self.theta.value() {
+            1.
+        } else {
+            0.
+        }
+    }
+}
+
+impl<T: Real> Cdf<T> for Bernoulli<T> {
+    fn cdf(&self, k: i64) -> T {
+        match k {
+            k if k < 0 => self.theta.constant(0.),
+            0 => -self.theta + 1.,
+            _ => self.theta.constant(1.),
+        }
+    }
+}
+
+/// The [binomial distribution](https://en.wikipedia.org/wiki/Binomial_distribution) with `n`
+/// trials and success probability `theta`.
+#[derive(Debug, Clone, Copy)]
+pub struct Binomial<T> {
+    pub n: i64,
+    pub theta: T,
+}
+
+impl<T: Real> Binomial<T> {
+    pub fn new(n: i64, theta: T) -> Self {
+        Self { n, theta }
+    }
+}
+
+impl<T: Real> Distribution<T> for Binomial<T> {
+    type Support = i64;
+
+    fn log_density(&self, k: i64) -> T {
+        if k < 0 || k > self.n {
+            return self.theta.constant(f64::NEG_INFINITY);
+        }
+        let log_choose = functions::lchoose(self.n as f64, k as f64);
+        self.unnormalized_log_density(k) + log_choose
+    }
+
+    fn unnormalized_log_density(&self, k: i64) -> T {
+        if self.theta < 0. || self.theta > 1. || self.n < 0 || k < 0 || k > self.n {
+            return self.theta.constant(f64::NEG_INFINITY);
+        }
+        // skip the terms with no counts, which would be 0 * -inf when theta is 0 or 1
+        match (k, self.n - k) {
+            (0, failures) => (-self.theta + 1.).ln() * failures as f64,
+            (successes, 0) => self.theta.ln() * successes as f64,
+            (successes, failures) => {
+                self.theta.ln() * successes as f64 + (-self.theta + 1.).ln() * failures as f64
+            }
+        }
+    }
+
+    fn sample(&self) -> f64 {
+        random::binomial(self.n, self.theta.value()) as f64
+    }
+}
+
+impl<T: Real + functions::IncompleteBeta> Cdf<T> for Binomial<T> {
+    fn cdf(&self, k: i64) -> T {
+        if k < 0 {
+            self.theta.constant(0.)
+        } else if k >= self.n {
+            self.theta.constant(1.)
+        } else {
+            let (a, b) = ((self.n - k) as f64, (k + 1) as f64);
+            let (a, b) = (self.theta.constant(a), self.theta.constant(b));
+            functions::beta_inc(a, b, -self.theta + 1.)
+        }
+    }
+
+    fn lccdf(&self, k: i64) -> T {
+        if k < 0 {
+            self.theta.constant(0.)
+        } else if k >= self.n {
+            self.theta.constant(f64::NEG_INFINITY)
+        } else {
+            let (a, b) = ((k + 1) as f64, (self.n - k) as f64);
+            let (a, b) = (self.theta.constant(a), self.theta.constant(b));
+            functions::beta_inc(a, b, self.theta).ln()
+        }
+    }
+}
+
+/// The [Poisson distribution](https://en.wikipedia.org/wiki/Poisson_distribution) with rate
+/// `lambda`.
+#[derive(Debug, Clone, Copy)]
+pub struct Poisson<T> {
+    pub lambda: T,
+}
+
+impl<T: Real> Poisson<T> {
+    pub fn new(lambda: T) -> Self {
+        Self { lambda }
+    }
+}
+
+impl<T: Real> Distribution<T> for Poisson<T> {
+    type Support = i64;
+
+    fn log_density(&self, k: i64) -> T {
+        if k < 0 {
+            return self.lambda.constant(f64::NEG_INFINITY);
+        }
+        self.unnormalized_log_density(k) - ln_factorial(k)
+    }
+
+    fn unnormalized_log_density(&self, k: i64) -> T {
+        if self.lambda <= 0. || k < 0 {
+            return self.lambda.constant(f64::NEG_INFINITY);
+        }
+        self.lambda.ln() * k as f64 - self.lambda
+    }
+
+    fn sample(&self) -> f64 {
+        random::poisson(self.lambda.value()) as f64
+    }
+}
+
+impl<T: Real + functions::IncompleteGamma> Cdf<T> for Poisson<T> {
+    fn cdf(&self, k: i64) -> T {
+        if k < 0 {
+            return self.lambda.constant(0.);
+        }
+        functions::gamma_q(self.lambda.constant(k as f64 + 1.), self.lambda)
+    }
+
+    fn lccdf(&self, k: i64) -> T {
+        if k < 0 {
+            return self.lambda.constant(0.);
+        }
+        functions::gamma_p(self.lambda.constant(k as f64 + 1.), self.lambda).ln()
+    }
+}
+
+/// The [negative binomial
+/// distribution](https://en.wikipedia.org/wiki/Negative_binomial_distribution) with mean `mu` and
+/// dispersion `phi`, so that the variance is `mu + mu^2 / phi`.
+#[derive(Debug, Clone, Copy)]
+pub struct NegativeBinomial<T> {
+    pub mu: T,
+    pub phi: T,
+}
+
+impl<T: Real> NegativeBinomial<T> {
+    pub fn new(mu: T, phi: T) -> Self {
+        Self { mu, phi }
+    }
+
+    /// The log probability of a zero count.
+    fn ln_zero(&self) -> T {
+        self.phi * (self.phi / (self.mu + self.phi)).ln()
+    }
+}
+
+impl<T: Real + functions::Gamma> Distribution<T> for NegativeBinomial<T> {
+    type Support = i64;
+
+    fn log_density(&self, k: i64) -> T {
+        if k < 0 {
+            return self.mu.constant(f64::NEG_INFINITY);
+        }
+        self.unnormalized_log_density(k) - ln_factorial(k)
+    }
+
+    fn unnormalized_log_density(&self, k: i64) -> T {
+        if self.mu <= 0. || self.phi <= 0. || k < 0 {
+            return self.mu.constant(f64::NEG_INFINITY);
+        }
+        ln_rising(self.phi, k) + self.ln_zero() + (self.mu / (self.mu + self.phi)).ln() * k as f64
+    }
+
+    fn sample(&self) -> f64 {
+        let (mu, phi) = (self.mu.value(), self.phi.value());
+        random::poisson(random::standard_gamma(phi) * mu / phi) as f64
+    }
+}
+
+impl<T: Real + functions::Gamma + functions::IncompleteBeta> Cdf<T> for NegativeBinomial<T> {
+    fn cdf(&self, k: i64) -> T {
+        if k < 0 {
+            return self.mu.constant(0.);
+        }
+        let b = self.mu.constant(k as f64 + 1.);
+        functions::beta_inc(self.phi, b, self.phi / (self.mu + self.phi))
+    }
+
+    fn lccdf(&self, k: i64) -> T {
+        if k < 0 {
+            return self.mu.constant(0.);
+        }
+        let a = self.mu.constant(k as f64 + 1.);
+        functions::beta_inc(a, self.phi, self.mu / (self.mu + self.phi)).ln()
+    }
+}
+
+/// The [beta-binomial distribution](https://en.wikipedia.org/wiki/Beta-binomial_distribution)
+/// with `n` trials and a Beta(`alpha`, `beta`) success probability.
+#[derive(Debug, Clone, Copy)]
+pub struct BetaBinomial<T> {
+    pub n: i64,
+    pub alpha: T,
+    pub beta: T,
+}
+
+impl<T: Real> BetaBinomial<T> {
+    pub fn new(n: i64, alpha: T, beta: T) -> Self {
+        Self { n, alpha, beta }
+    }
+}
+
+impl<T: Real + functions::Gamma> Distribution<T> for BetaBinomial<T> {
+    type Support = i64;
+
+    fn log_density(&self, k: i64) -> T {
+        if k < 0 || k > self.n {
+            return self.alpha.constant(f64::NEG_INFINITY);
+        }
+        let log_choose = functions::lchoose(self.n as f64, k as f64);
+        self.unnormalized_log_density(k) + log_choose
+    }
+
+    fn unnormalized_log_density(&self, k: i64) -> T {
+        if self.alpha <= 0. || self.beta <= 0. || self.n < 0 || k < 0 || k > self.n {
+            return self.alpha.constant(f64::NEG_INFINITY);
+        }
+        ln_rising(self.alpha, k) + ln_rising(self.beta, self.n - k)
+            - ln_rising(self.alpha + self.beta, self.n)
+    }
+
+    fn sample(&self) -> f64 {
+        let theta = random::beta(self.alpha.value(), self.beta.value());
+        random::binomial(self.n, theta) as f64
+    }
+}
+
+impl<T: Real + functions::Gamma> Cdf<T> for BetaBinomial<T> {
+    fn cdf(&self, k: i64) -> T {
+        if k < 0 {
+            self.alpha.constant(0.)
+        } else if k >= self.n {
+            self.alpha.constant(1.)
+        } else {
+            sum_masses(self, k)
+        }
+    }
+}
+
+/// The [geometric distribution](https://en.wikipedia.org/wiki/Geometric_distribution) of the
+/// number of failures before the first success, with success probability `theta`.
+#[derive(Debug, Clone, Copy)]
+pub struct Geometric<T> {
+    pub theta: T,
+}
+
+impl<T: Real> Geometric<T> {
+    pub fn new(theta: T) -> Self {
+        Self { theta }
+    }
+}
+
+impl<T: Real> Distribution<T> for Geometric<T> {
+    type Support = i64;
+
+    fn log_density(&self, k: i64) -> T {
+        if self.theta <= 0. || self.theta > 1. || k < 0 {
+            return self.theta.constant(f64::NEG_INFINITY);
+        }
+        match k {
+            // avoid 0 * -inf when theta is 1
+            0 => self.theta.ln(),
+            _ => self.theta.ln() + (-self.theta + 1.).ln() * k as f64,
+        }
+    }
+
+    fn sample(&self) -> f64 {
+        let theta = self.theta.value();
+        if theta >= 1. {
+            return 0.;
+        }
+        (random::uniform().ln() / (1. - theta).ln()).floor()
+    }
+}
+
+impl<T: Real> Cdf<T> for Geometric<T> {
+    fn cdf(&self, k: i64) -> T {
+        if k < 0 {
+            return self.theta.constant(0.);
+        }
+        -(-self.theta + 1.).powi(k as i32 + 1) + 1.
+    }
+
+    fn lccdf(&self, k: i64) -> T {
+        if k < 0 {
+            return self.theta.constant(0.);
+        }
+        (-self.theta + 1.).ln() * (k + 1) as f64
+    }
+}
+
+/// The [hypergeometric distribution](https://en.wikipedia.org/wiki/Hypergeometric_distribution)
+/// of the number of successes in `draws` draws without replacement, from a population of
+/// `successes` successes and `failures` failures. It has no continuous parameters, so it is only
+/// a `Distribution<f64>`.
+#[derive(Debug, Clone, Copy)]
+pub struct Hypergeometric {
+    pub draws: i64,
+    pub successes: i64,
+    pub failures: i64,
+}
+
+impl Hypergeometric {
+    pub fn new(draws: i64, successes: i64, failures: i64) -> Self {
+        Self {
+            draws,
+            successes,
+            failures,
+        }
+    }
+}
+
+impl Distribution<f64> for Hypergeometric {
+    type Support = i64;
+
+    fn log_density(&self, k: i64) -> f64 {
+        let (n, a, b) = (self.draws, self.successes, self.failures);
+        if a < 0 || b < 0 || n < 0 || n > a + b || k < 0 || k > a || n - k > b || k > n {
+            return f64::NEG_INFINITY;
+        }
+        let ln_choose = |n: i64, k: i64| functions::lchoose(n as f64, k as f64);
+        ln_choose(a, k) + ln_choose(b, n - k) - ln_choose(a + b, n)
+    }
+
+    fn sample(&self) -> f64 {
+        let (mut a, mut b) = (self.successes, self.failures);
+        let mut k = 0;
+        for _ in 0..self.draws {
+            if alea::f64() * ((a + b) as f64) < a as f64 {
+                k += 1;
+                a -= 1;
+            } else {
+                b -= 1;
+            }
+        }
+        k as f64
+    }
+}
+
+impl Cdf<f64> for Hypergeometric {
+    fn cdf(&self, k: i64) -> f64 {
+        if k < 0 {
+            0.
+        } else if k >= self.draws {
+            1.
+        } else {
+            sum_masses(self, k)
+        }
+    }
+}
+
+/// A Poisson distribution with rate `lambda` and an extra probability `theta` of a zero count.
+#[derive(Debug, Clone, Copy)]
+pub struct ZeroInflatedPoisson<T> {
+    pub theta: T,
+    pub lambda: T,
+}
+
+impl<T: Real> ZeroInflatedPoisson<T> {
+    pub fn new(theta: T, lambda: T) -> Self {
+        Self { theta, lambda }
+    }
+}
+
+impl<T: Real> Distribution<T> for ZeroInflatedPoisson<T> {
+    type Support = i64;
+
+    fn log_density(&self, k: i64) -> T {
+        if k < 0 {
+            return self.theta.constant(f64::NEG_INFINITY);
+        }
+        self.unnormalized_log_density(k) - ln_factorial(k)
+    }
+
+    fn unnormalized_log_density(&self, k: i64) -> T {
+        if self.theta < 0. || self.theta > 1. || self.lambda <= 0. || k < 0 {
+            return self.theta.constant(f64::NEG_INFINITY);
+        }
+        let poisson = Poisson::new(self.lambda);
+        match k {
+            0 => (self.theta + (-self.theta + 1.) * (-self.lambda).exp()).ln(),
+            _ => (-self.theta + 1.).ln() + poisson.unnormalized_log_density(k),
+        }
+    }
+
+    fn sample(&self) -> f64 {
+        if alea::f64() < self.theta.value() {
+            0.
+        } else {
+            random::poisson(self.lambda.value()) as f64
+        }
+    }
+}
+
+impl<T: Real> Cdf<T> for ZeroInflatedPoisson<T> {
+    fn cdf(&self, k: i64) -> T {
+        if k < 0 {
+            self.theta.constant(0.)
+        } else {
+            sum_masses(self, k)
+        }
+    }
+}
+
+/// A negative binomial distribution with mean `mu` and dispersion `phi`, and an extra
+/// probability `theta` of a zero count.
+#[derive(Debug, Clone, Copy)]
+pub struct ZeroInflatedNegativeBinomial<T> {
+    pub theta: T,
+    pub mu: T,
+    pub phi: T,
+}
+
+impl<T: Real> ZeroInflatedNegativeBinomial<T> {
+    pub fn new(theta: T, mu: T, phi: T) -> Self {
+        Self { theta, mu, phi }
+    }
+}
+
+impl<T: Real + functions::Gamma> Distribution<T> for ZeroInflatedNegativeBinomial<T> {
+    type Support = i64;
+
+    fn log_density(&self, k: i64) -> T {
+        if k < 0 {
+            return self.theta.constant(f64::NEG_INFINITY);
+        }
+        self.unnormalized_log_density(k) - ln_factorial(k)
+    }
+
+    fn unnormalized_log_density(&self, k: i64) -> T {
+        if self.theta < 0. || self.theta > 1. || self.mu <= 0. || self.phi <= 0. || k < 0 {
+            return self.theta.constant(f64::NEG_INFINITY);
+        }
+        let nb = NegativeBinomial::new(self.mu, self.phi);
+        match k {
+            0 => (self.theta + (-self.theta + 1.) * nb.ln_zero().exp()).ln(),
+            _ => (-self.theta + 1.).ln() + nb.unnormalized_log_density(k),
+        }
+    }
+
+    fn sample(&self) -> f64 {
+        if alea::f64() < self.theta.value() {
+            0.
+        } else {
+            NegativeBinomial::new(self.mu, self.phi).sample()
+        }
+    }
+}
+
+impl<T: Real + functions::Gamma + functions::IncompleteBeta> Cdf<T>
+    for ZeroInflatedNegativeBinomial<T>
+{
+    fn cdf(&self, k: i64) -> T {
+        if k < 0 {
+            return self.theta.constant(0.);
+        }
+        let nb = NegativeBinomial::new(self.mu, self.phi);
+        self.theta + (-self.theta + 1.) * nb.cdf(k)
+    }
+}
+
+/// A hurdle model where a count is zero with probability `theta`, and otherwise follows a
+/// Poisson distribution with rate `lambda` truncated to positive counts.
+#[derive(Debug, Clone, Copy)]
+pub struct HurdlePoisson<T> {
+    pub theta: T,
+    pub lambda: T,
+}
+
+impl<T: Real> HurdlePoisson<T> {
+    pub fn new(theta: T, lambda: T) -> Self {
+        Self { theta, lambda }
+    }
+}
+
+impl<T: Real> Distribution<T> for HurdlePoisson<T> {
+    type Support = i64;
+
+    fn log_density(&self, k: i64) -> T {
+        if k < 0 {
+            return self.theta.constant(f64::NEG_INFINITY);
+        }
+        self.unnormalized_log_density(k) - ln_factorial(k)
+    }
+
+    fn unnormalized_log_density(&self, k: i64) -> T {
+        if self.theta < 0. || self.theta > 1. || self.lambda <= 0. || k < 0 {
+            return self.theta.constant(f64::NEG_INFINITY);
+        }
+        match k {
+            0 => self.theta.ln(),
+            _ => {
+                (-self.theta + 1.).ln() + Poisson::new(self.lambda).unnormalized_log_density(k)
+                    - functions::log1m_exp(-self.lambda)
+            }
+        }
+    }
+
+    fn sample(&self) -> f64 {
+        if alea::f64() < self.theta.value() {
+            return 0.;
+        }
+        loop {
+            let k = random::poisson(self.lambda.value());
+            if k > 0 {
+                return k as f64;
+            }
+        }
+    }
+}
+
+impl<T: Real> Cdf<T> for HurdlePoisson<T> {
+    fn cdf(&self, k: i64) -> T {
+        if k < 0 {
+            self.theta.constant(0.)
+        } else {
+            sum_masses(self, k)
+        }
+    }
+}
+
+/// A hurdle model where a count is zero with probability `theta`, and otherwise follows a negative
+/// binomial distribution with mean `mu` and dispersion `phi` truncated to positive counts.
+#[derive(Debug, Clone, Copy)]
+pub struct HurdleNegativeBinomial<T> {
+    pub theta: T,
+    pub mu: T,
+    pub phi: T,
+}
+
+impl<T: Real> HurdleNegativeBinomial<T> {
+    pub fn new(theta: T, mu: T, phi: T) -> Self {
+        Self { theta, mu, phi }
+    }
+}
+
+impl<T: Real + functions::Gamma> Distribution<T> for HurdleNegativeBinomial<T> {
+    type Support = i64;
+
+    fn log_density(&self, k: i64) -> T {
+        if k < 0 {
+            return self.theta.constant(f64::NEG_INFINITY);
+        }
+        self.unnormalized_log_density(k) - ln_factorial(k)
+    }
+
+    fn unnormalized_log_density(&self, k: i64) -> T {
+        if self.theta < 0. || self.theta > 1. || self.mu <= 0. || self.phi <= 0. || k < 0 {
+            return self.theta.constant(f64::NEG_INFINITY);
+        }
+        let nb = NegativeBinomial::new(self.mu, self.phi);
+        match k {
+            0 => self.theta.ln(),
+            _ => {
+                (-self.theta + 1.).ln() + nb.unnormalized_log_density(k)
+                    - functions::log1m_exp(nb.ln_zero())
+            }
+        }
+    }
+
+    fn sample(&self) -> f64 {
+        if alea::f64() < self.theta.value() {
+            return 0.;
+        }
+        let nb = NegativeBinomial::new(self.mu, self.phi);
+        loop {
+            let k = nb.sample();
+            if k > 0. {
+                return k;
+            }
+        }
+    }
+}
+
+impl<T: Real + functions::Gamma + functions::IncompleteBeta> Cdf<T> for HurdleNegativeBinomial<T> {
+    fn cdf(&self, k: i64) -> T {
+        if k < 0 {
+            return self.theta.constant(0.);
+        }
+        if k == 0 {
+            return self.theta;
+        }
+        // the negative binomial truncated to positive counts
+        let nb = NegativeBinomial::new(self.mu, self.phi);
+        let zero = nb.ln_zero().exp();
+        self.theta + (-self.theta + 1.) * (nb.cdf(k) - zero) / (-zero + 1.)
+    }
+}
+
+/// The [categorical distribution](https://en.wikipedia.org/wiki/Categorical_distribution) over
+/// the categories 0, 1, ..., K - 1, parameterized by their probabilities or by logits.
+///
+/// If the probabilities are negative or do not sum to 1, the log density is -inf.
+#[derive(Debug, Clone)]
+pub struct Categorical<T> {
+    log_theta: Vec<T>,
+}
+
+impl<T: Real> Categorical<T> {
+    /// Creates a categorical distribution with the probabilities `theta`.
+    pub fn new(theta: &[T]) -> Self {
+        assert!(!theta.is_empty(), "There must be at least one category.");
+        let log_theta = if is_simplex(theta) {
+            theta.iter().map(|p| p.ln()).collect()
+        } else {
+            theta
+                .iter()
+                .map(|p| p.constant(f64::NEG_INFINITY))
+                .collect()
+        };
+        Self { log_theta }
+    }
+
+    /// Creates a categorical distribution with the probabilities `softmax(eta)`.
+    pub fn with_logits(eta: &[T]) -> Self {
+        assert!(!eta.is_empty(), "There must be at least one category.");
+        Self {
+            log_theta: functions::log_softmax(eta),
+        }
+    }
+
+    pub fn categories(&self) -> usize {
+        self.log_theta.len()
+    }
+}
+
+impl<T: Real> Distribution<T> for Categorical<T> {
+    type Support = i64;
+
+    fn log_density(&self, k: i64) -> T {
+        if k < 0 || k >= self.categories() as i64 {
+            return self.log_theta[0].constant(f64::NEG_INFINITY);
+        }
+        self.log_theta[k as usize]
+    }
+
+    fn sample(&self) -> f64 {
+        let u = alea::f64();
+        let mut cumulative = 0.;
+        for (k, p) in self.log_theta.iter().enumerate() {
+            cumulative += p.value().exp();
+            if u < cumulative {
+                return k as f64;
+            }
+        }
+        assert!(cumulative > 0., "Cannot sample with invalid probabilities.");
+        (self.categories() - 1) as f64
+    }
+}
+
+impl<T: Real> Cdf<T> for Categorical<T> {
+    fn cdf(&self, k: i64) -> T {
+        if k < 0 {
+            self.log_theta[0].constant(0.)
+        } else if k >= self.categories() as i64 - 1 {
+            self.log_theta[0].constant(1.)
+        } else {
+            sum_masses(self, k)
+        }
+    }
+}
+
+/// Calculates the log probability of the category `k` of an ordinal regression with linear
+/// predictor `eta` and increasing `cutpoints`, where `ln_cdf` is the log of a symmetric CDF.
+fn ordered_log_mass<T: Real>(eta: T, cutpoints: &[T], k: i64, ln_cdf: fn(T) -> T) -> T {
+    let n = cutpoints.len() as i64;
+    if k < 0 || k > n || cutpoints.windows(2).any(|c| c[1] - c[0] <= 0.) {
+        return eta.constant(f64::NEG_INFINITY);
+    }
+    if k == 0 {
+        ln_cdf(cutpoints[0] - eta)
+    } else if k == n {
+        ln_cdf(eta - cutpoints[k as usize - 1])
+    } else {
+        // ln(F(a) - F(b)) for a > b
+        let upper = ln_cdf(eta - cutpoints[k as usize - 1]);
+        let lower = ln_cdf(eta - cutpoints[k as usize]);
+        functions::log_diff_exp(upper, lower)
+    }
+}
+
+/// Draws the category of an ordinal regression whose latent variable is `latent`.
+fn ordered_sample<T: Real>(latent: f64, cutpoints: &[T]) -> f64 {
+    cutpoints.iter().filter(|c| c.value() < latent).count() as f64
+}
+
+/// Calculates ln(logistic(x)), without overflowing for large |x|.
+fn ln_logistic<T: Real>(x: T) -> T {
+    -functions::log1p_exp(-x)
+}
+
+/// The [ordered logistic
+/// distribution](https://en.wikipedia.org/wiki/Ordered_logit) over the categories 0, 1, ..., K,
+/// with linear predictor `eta` and K increasing `cutpoints`.
+///
+/// If the cutpoints are not strictly increasing, the log density is -inf.
+#[derive(Debug, Clone)]
+pub struct OrderedLogistic<T> {
+    pub eta: T,
+    pub cutpoints: Vec<T>,
+}
+
+impl<T: Real> OrderedLogistic<T> {
+    pub fn new(eta: T, cutpoints: &[T]) -> Self {
+        assert!(
+            !cutpoints.is_empty(),
+            "There must be at least one cutpoint."
+        );
+        Self {
+            eta,
+            cutpoints: cutpoints.to_vec(),
+        }
+    }
+}
+
+impl<T: Real> Distribution<T> for OrderedLogistic<T> {
+    type Support = i64;
+
+    fn log_density(&self, k: i64) -> T {
+        ordered_log_mass(self.eta, &self.cutpoints, k, ln_logistic)
+    }
+
+    fn sample(&self) -> f64 {
+        let u = random::uniform();
+        ordered_sample(self.eta.value() + (u / (1. - u)).ln(), &self.cutpoints)
+    }
+}
+
+impl<T: Real> Cdf<T> for OrderedLogistic<T> {
+    fn cdf(&self, k: i64) -> T {
+        self.lcdf(k).exp()
+    }
+
+    fn lcdf(&self, k: i64) -> T {
+        if k < 0 {
+            self.eta.constant(f64::NEG_INFINITY)
+        } else if k >= self.cutpoints.len() as i64 {
+            self.eta.constant(0.)
+        } else {
+            ln_logistic(self.cutpoints[k as usize] - self.eta)
+        }
+    }
+}
+
+/// The [ordered probit distribution](https://en.wikipedia.org/wiki/Ordered_probit) over the
+/// categories 0, 1, ..., K, with linear predictor `eta` and K increasing `cutpoints`.
+///
+/// If the cutpoints are not strictly increasing, the log density is -inf.
+#[derive(Debug, Clone)]
+pub struct OrderedProbit<T> {
+    pub eta: T,
+    pub cutpoints: Vec<T>,
+}
+
+impl<T: Real> OrderedProbit<T> {
+    pub fn new(eta: T, cutpoints: &[T]) -> Self {
+        assert!(
+            !cutpoints.is_empty(),
+            "There must be at least one cutpoint."
+        );
+        Self {
+            eta,
+            cutpoints: cutpoints.to_vec(),
+        }
+    }
+}
+
+impl<T: Real + functions::Erf> Distribution<T> for OrderedProbit<T> {
+    type Support = i64;
+
+    fn log_density(&self, k: i64) -> T {
+        ordered_log_mass(self.eta, &self.cutpoints, k, functions::log_phi)
+    }
+
+    fn sample(&self) -> f64 {
+        ordered_sample(
+            self.eta.value() + random::standard_normal(),
+            &self.cutpoints,
+        )
+    }
+}
+
+impl<T: Real + functions::Erf> Cdf<T> for OrderedProbit<T> {
+    fn cdf(&self, k: i64) -> T {
+        self.lcdf(k).exp()
+    }
+
+    fn lcdf(&self, k: i64) -> T {
+        if k < 0 {
+            self.eta.constant(f64::NEG_INFINITY)
+        } else if k >= self.cutpoints.len() as i64 {
+            self.eta.constant(0.)
+        } else {
+            functions::log_phi(self.cutpoints[k as usize] - self.eta)
+        }
+    }
+}
@@ -0,0 +1,143 @@
+//! Linear algebra on `f64` and `Var` matrices.
+//!
+//! Matrices are stored as rows. Lower triangular matrices only store the entries on and below the
+//! diagonal, so row `i` has `i + 1` entries.
+
+use crate::functions::Real;
+
+/// Calculates the lower triangular Cholesky factor of a symmetric matrix, or `None` if it is not
+/// positive definite. Only the lower triangle of `a` is used.
+pub(crate) fn cholesky<T: Real>(a: &[Vec<T>]) -> Option<Vec<Vec<T>>> {
+    let n = a.len();
+    let mut l: Vec<Vec<T>> = Vec::with_capacity(n);
+    for i in 0..n {
+        let mut row = Vec::with_capacity(i + 1);
+        for j in 0..i {
+            let s = (0..j).fold(a[i][j], |s, k| s - row[k] * l[j][k]);
+            row.push(s / l[j][j]);
+        }
+        let s = (0..i).fold(a[i][i], |s, k| s - row[k] * row[k]);
+        if s <= 0. {
+            return None;
+        }
+        row.push(s.sqrt());
+        l.push(row);
+    }
+    Some(l)
+}
+
+/// Copies the lower triangle of a square matrix.
+pub(crate) fn lower<T: Real>(a: &[Vec<T>]) -> Vec<Vec<T>> {
+    a.iter()
+        .enumerate()
+        .map(|(i, row)| row[..=i].to_vec())
+        .collect()
+}
+
+/// Solves `L x = b` for lower triangular `L`.
+pub(crate) fn forward_solve<T: Real>(l: &[Vec<T>], b: &[T]) -> Vec<T> {
+    let mut x: Vec<T> = Vec::with_capacity(b.len());
+    for i in 0..b.len() {
+        let s = (0..i).fold(b[i], |s, k| s - l[i][k] * x[k]);
+        x.push(s / l[i][i]);
+    }
+    x
+}
+
+/// Solves `L^T x = b` for lower triangular `L`.
+pub(crate) fn backward_solve<T: Real>(l: &[Vec<T>], b: &[T]) -> Vec<T> {
+    let n = b.len();
+    let mut x = b.to_vec();
+    for i in (0..n).rev() {
+        let s = (i + 1..n).fold(b[i], |s, k| s - l[k][i] * x[k]);
+        x[i] = s / l[i][i];
+    }
+    x
+}
+
+/// Calculates `L^T x` for lower triangular `L`.
+pub(crate) fn transpose_mul<T: Real>(l: &[Vec<T>], x: &[T]) -> Vec<T> {
+    let n = x.len();
+    (0..n)
+        .map(|j| (j + 1..n).fold(l[j][j] * x[j], |s, i| s + l[i][j] * x[i]))
+        .collect()
+}
+
+/// Calculates `L x` for lower triangular `L`.
+pub(crate) fn mul<T: Real>(l: &[Vec<T>], x: &[T]) -> Vec<T> {
+    l.iter()
+        .map(|row| (1..row.len()).fold(row[0] * x[0], |s, k| s + row[k] * x[k]))
+        .collect()
+}
+
+/// Calculates the sum of the logs of the diagonal of a lower triangular matrix, which is half of
+/// the log determinant of `L L^T`.
+pub(crate) fn log_diagonal<T: Real>(l: &[Vec<T>]) -> T {
+    (1..l.len()).fold(l[0][0].ln(), |s, i| s + l[i][i].ln())
+}
+
+/// Calculates the sum of squares of a vector.
+pub(crate) fn dot_self<T: Real>(x: &[T]) -> T {
+    x[1..].iter().fold(x[0] * x[0], |s, &xi| s + xi * xi)
+}
+
+/// Converts a matrix to `f64`.
+pub(crate) fn values<T: Real>(a: &[Vec<T>]) -> Vec<Vec<f64>> {
+    a.iter()
+        .map(|row| row.iter().map(|x| x.value()).collect())
+        .collect()
+}
+
+/// Calculates `A A^T`, where the rows of `A` may be ragged, such as for a lower triangular matrix.
+/// Missing entries are treated as zeros.
+pub(crate) fn gram<T: Real>(a: &[Vec<T>]) -> Vec<Vec<T>> {
+    a.iter()
+        .map(|ai| {
+            a.iter()
+                .map(|aj| {
+                    let n = ai.len().min(aj.len());
+                    (1..n).fold(ai[0] * aj[0], |s, k| s + ai[k] * aj[k])
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Calculates `A B` for lower triangular `A` and `B`.
+pub(crate) fn lower_mul<T: Real>(a: &[Vec<T>], b: &[Vec<T>]) -> Vec<Vec<T>> {
+    a.iter()
+        .enumerate()
+        .map(|(i, row)| {
+            (0..=i)
+                .map(|j| (j + 1..=i).fold(row[j] * b[j][j], |s, k| s + row[k] * b[k][j]))
+                .collect()
+        })
+        .collect()
+}
+
+/// Calculates `tr((L L^T)^-1 M M^T)`, the squared Frobenius norm of `L^-1 M`, for lower
+/// triangular `L` and `M`.
+pub(crate) fn trace_solve<T: Real>(l: &[Vec<T>], m: &[Vec<T>]) -> T {
+    let n = m.len();
+    let zero = m[0][0].constant(0.);
+    (0..n)
+        .map(|j| {
+            let column = (0..n)
+                .map(|i| if i < j { zero } else { m[i][j] })
+                .collect::<Vec<_>>();
+            dot_self(&forward_solve(l, &column))
+        })
+        .fold(zero, |s, x| s + x)
+}
+
+/// Pads the rows of a lower triangular matrix with zeros, to get a square matrix.
+pub(crate) fn square<T: Real>(l: &[Vec<T>]) -> Vec<Vec<T>> {
+    let n = l.len();
+    l.iter()
+        .map(|row| {
+            let mut row = row.clone();
+            row.resize(n, row[0].constant(0.));
+            row
+        })
+        .collect()
+}
@@ -0,0 +1,105 @@
+//! Finite mixtures.
+
+use super::{is_simplex, Cdf, Distribution};
+use crate::functions::{self, Real};
+
+/// A finite mixture of `components` of the same family, with mixing weights given as a simplex or
+/// as logits. Mixtures of different families can be written with `functions::log_mix`.
+///
+/// If the weights are negative or do not sum to 1, the log density is -inf.
+#[derive(Debug, Clone)]
+pub struct Mixture<T, D> {
+    log_weights: Vec<T>,
+    components: Vec<D>,
+}
+
+impl<T: Real, D: Distribution<T>> Mixture<T, D> {
+    /// Creates a mixture with the mixing weights `theta`.
+    pub fn new(theta: &[T], components: Vec<D>) -> Self {
+        assert_eq!(theta.len(), components.len(), "Dimensions must match.");
+        assert!(!theta.is_empty(), "There must be at least one component.");
+        let log_weights = if is_simplex(theta) {
+            theta.iter().map(|w| w.ln()).collect()
+        } else {
+            theta
+                .iter()
+                .map(|w| w.constant(f64::NEG_INFINITY))
+                .collect()
+        };
+        Self {
+            log_weights,
+            components,
+        }
+    }
+
+    /// Creates a mixture with the mixing weights `softmax(eta)`.
+    pub fn with_logits(eta: &[T], components: Vec<D>) -> Self {
+        assert_eq!(eta.len(), components.len(), "Dimensions must match.");
+        assert!(!eta.is_empty(), "There must be at least one component.");
+        Self {
+            log_weights: functions::log_softmax(eta),
+            components,
+        }
+    }
+
+    pub fn components(&self) -> &[D] {
+        &self.components
+    }
+
+    /// Combines the log densities of the components, calculated by `f`.
+    fn mix<F: Fn(&D) -> T>(&self, f: F) -> T {
+        let terms = self
+            .log_weights
+            .iter()
+            .zip(&self.components)
+            .map(|(&w, d)| w + f(d))
+            .collect::<Vec<_>>();
+        functions::log_sum_exp(&terms)
+    }
+}
+
+impl<T: Real, D: Distribution<T>> Distribution<T> for Mixture<T, D>
+where
+    D::Support: Copy,
+{
+    type Support = D::Support;
+
+    fn log_density(&self, x: D::Support) -> T {
+        self.mix(|d| d.log_density(x))
+    }
+
+    /// The components are of the same family, so they drop the same terms.
+    fn unnormalized_log_density(&self, x: D::Support) -> T {
+        self.mix(|d| d.unnormalized_log_density(x))
+    }
+
+    fn sample(&self) -> f64 {
+        let u = alea::f64();
+        let mut cumulative = 0.;
+        for (w, d) in self.log_weights.iter().zip(&self.components) {
+            cumulative += w.value().exp();
+            if u < cumulative {
+                return d.sample();
+            }
+        }
+        assert!(cumulative > 0., "Cannot sample with invalid weights.");
+        self.components[self.components.len() - 1].sample()
+    }
+}
+
+impl<T: Real, D: Cdf<T>> Cdf<T> for Mixture<T, D>
+where
+    D::Support: Copy,
+{
+    fn cdf(&self, x: D::Support) -> T {
+        self.lcdf(x).exp()
+    }
+
+    fn lcdf(&self, x: D::Support) -> T {
+        self.mix(|d| d.lcdf(x))
+    }
+
+    fn lccdf(&self, x: D::Support) -> T {
+        self.mix(|d| d.lccdf(x))
+    }
+}
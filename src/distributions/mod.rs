@@ -0,0 +1,1578 @@
+//! Common distributions.
+//!
+//! Each distribution is a struct (e.g. `Normal`) that implements `Distribution`, and `Cdf` where
+//! its cumulative distribution function is available. Their parameters are either all `f64` or
+//! all `Var`.
+//!
+//! Each distribution also has a pair of macros for use in `#[model]` functions, which accept any
+//! mix of `f64` and `Var` arguments. The plain macros (e.g. `normal!`) drop terms that do not
+//! depend on the variable or the parameters, which is all that is needed for MCMC. The `_lpdf` and
+//! `_lpmf` variants (e.g. `normal_lpdf!`) are fully normalized, for when the actual value of the
+//! log density matters, such as for marginal likelihoods and model comparison.
+//!
+//! The discrete macros cast their variable (and any numbers of trials or draws) to `i64`, so counts
+//! from a `Data::IntArray` can be passed in directly.
+//!
+//! The macros of the most common univariate distributions (`normal!`, `lognormal!`,
+//! `exponential!`, `laplace!`, `cauchy!`, `bernoulli!`, `binomial!` and `poisson!`) are
+//! vectorized: any argument may be a slice, `Vec` or `Vector` instead of a scalar, and scalars are
+//! broadcast, e.g. `normal!(y; mu, sigma)` for a vector of observations `y` and means `mu`. The
+//! summed log density is computed in one pass and recorded with one tape node per `Var` argument,
+//! which is much faster than looping over the observations. See `vectorized`.
+//!
+//! Univariate distributions with a CDF can be truncated to an interval with `Truncated`, and their
+//! macros take an optional Stan-like truncation, e.g. `normal!(y; mu, sigma; T[0., ])` for a
+//! normal truncated below at 0, or `poisson!(k; lambda; T[1, ])` for a zero-truncated Poisson.
+//!
+//! Finite mixtures of components of the same family are given by `Mixture`, and mixtures of any
+//! log densities by `functions::log_mix`, e.g. `target += log_mix(&theta, &[lp_1, lp_2]);` in a
+//! model.
+//!
+//! Censored observations, which are only known to be below, above or between some values, are
+//! modeled with `Censoring`, or by ending a macro with `left`, `right` or `interval`, e.g.
+//! `normal!(limit; mu, sigma; left)` for an observation below a detection limit, or
+//! `weibull!(lower, upper; k, lambda; interval)` for an event time between two visits.
+//!
+//! Random draws use the same parameterization as the log densities, either through `sample` or
+//! with the `_rng` macros, which take the parameters of the corresponding macro without the
+//! variable, e.g. `normal_rng!(mu, sigma)` or `binomial_rng!(n, theta)`. They are meant for
+//! posterior predictive checks and simulation-based calibration, e.g. in the generated quantities
+//! of a model. Draws on the current thread are reproducible after `set_seed`.
+//!
+//! Angles have the circular `VonMises`, `WrappedNormal` and `WrappedCauchy` distributions, which
+//! take any real angle and wrap it onto [-π, π), and unit vectors have `VonMisesFisher`.
+//!
+//! Values outside of the support of a distribution, as well as invalid parameters, have a log
+//! density of -inf instead of panicking. Samplers then reject such proposals and carry on.
+
+mod censored;
+mod continuous;
+mod discrete;
+mod linalg;
+mod mixture;
+mod multivariate;
+mod random;
+mod truncated;
+pub mod vectorized;
+
+pub use censored::*;
+pub use continuous::*;
+pub use discrete::*;
+pub use mixture::*;
+pub use multivariate::*;
+pub use random::set_seed;
+pub use truncated::*;
+
+use crate::functions::Real;
+use reverse::Var;
+
+/// A univariate distribution whose parameters are of type `T`, which is either `f64` or `Var`.
+pub trait Distribution<T: Real> {
+    /// The type of the variable: `T` for continuous distributions and `i64` for discrete ones.
+    type Support;
+
+    /// The log probability density (or mass) function. This is -inf for values outside of the
+    /// support and for invalid parameters.
+    fn log_density(&self, x: Self::Support) -> T;
+
+    /// The log probability density (or mass) function, without the terms that only depend on the
+    /// variable. These terms are constant in MCMC, so they can be dropped there.
+    fn unnormalized_log_density(&self, x: Self::Support) -> T {
+        self.log_density(x)
+    }
+
+    /// Draws a random sample.
+    fn sample(&self) -> f64;
+}
+
+/// A distribution with a known cumulative distribution function.
+pub trait Cdf<T: Real>: Distribution<T> {
+    /// The cumulative distribution function, P(X <= x).
+    fn cdf(&self, x: Self::Support) -> T;
+
+    /// The log of the cumulative distribution function.
+    fn lcdf(&self, x: Self::Support) -> T {
+        self.cdf(x).ln()
+    }
+
+    /// The log of the complementary cumulative distribution function, P(X > x).
+    fn lccdf(&self, x: Self::Support) -> T {
+        (-self.cdf(x) + 1.).ln()
+    }
+}
+
+/// A distribution over vectors or matrices whose parameters are of type `T`, which is either `f64`
+/// or `Var`.
+pub trait Multivariate<T: Real> {
+    /// The type of the variable, e.g. `[T]` for a distribution over vectors.
+    type Support: ?Sized;
+    /// The type of a random sample, e.g. `Vec<f64>` for a distribution over vectors.
+    type Sample;
+
+    /// The log probability density (or mass) function. This is -inf for values outside of the
+    /// support and for invalid parameters.
+    fn log_density(&self, x: &Self::Support) -> T;
+
+    /// The log probability density (or mass) function, without the terms that only depend on the
+    /// variable.
+    fn unnormalized_log_density(&self, x: &Self::Support) -> T {
+        self.log_density(x)
+    }
+
+    /// Draws a random sample.
+    fn sample(&self) -> Self::Sample;
+}
+
+/// The type of a univariate variable, which is `f64` or `Var` for continuous distributions and
+/// `i64` for discrete ones.
+pub trait Variate: Copy {
+    /// The value as an `f64`, for comparing with bounds.
+    fn to_f64(&self) -> f64;
+    /// The largest value below `self` for a discrete variable, or `self` for a continuous one, so
+    /// that `P(X < x) = cdf(x.predecessor())`.
+    fn predecessor(self) -> Self;
+}
+
+impl Variate for f64 {
+    fn to_f64(&self) -> f64 {
+        *self
+    }
+
+    fn predecessor(self) -> Self {
+        self
+    }
+}
+
+impl<'a> Variate for Var<'a> {
+    fn to_f64(&self) -> f64 {
+        self.val()
+    }
+
+    fn predecessor(self) -> Self {
+        self
+    }
+}
+
+impl Variate for i64 {
+    fn to_f64(&self) -> f64 {
+        *self as f64
+    }
+
+    fn predecessor(self) -> Self {
+        self - 1
+    }
+}
+
+/// Checks that `theta` is a probability vector, within rounding.
+fn is_simplex<T: Real>(theta: &[T]) -> bool {
+    let sum: f64 = theta.iter().map(|p| p.value()).sum();
+    theta.iter().all(|&p| p >= 0.) && (sum - 1.).abs() < 1e-8
+}
+
+/// Checks that `actual` matches the reference value `expected` to about 12 digits, for the tests
+/// of the distributions.
+#[cfg(test)]
+pub(super) fn assert_close(actual: f64, expected: f64) {
+    assert!(
+        (actual - expected).abs() <= 1e-12 * expected.abs().max(1.),
+        "{} != {}",
+        actual,
+        expected
+    );
+}
+
+/// Evaluates the log density of a distribution truncated to [`lower`, `upper`], for the
+/// `T[lower, upper]` arms of the distribution macros. Either bound may be left out.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! truncate {
+    (@bound) => {
+        None
+    };
+    (@bound $bound: expr) => {
+        Some($bound)
+    };
+    ( $d: expr, $x: expr, $method: ident, [$($lower: expr)?], [$($upper: expr)?] ) => {{
+        let lower = $crate::truncate!(@bound $($lower)?);
+        let upper = $crate::truncate!(@bound $($upper)?);
+        let d = $crate::distributions::Truncated::new($d, lower, upper);
+        $crate::distributions::Distribution::$method(&d, $x)
+    }};
+}
+
+/// Generates the arms shared by the univariate distribution macros: the log density
+/// `dist!(x; params...)`, the truncated `dist!(x; params...; T[lower, upper])`, and the censored
+/// `dist!(x; params...; left)`, `dist!(x; params...; right)` and
+/// `dist!(lower, upper; params...; interval)`. `method` is the `Distribution` method giving the
+/// log density, which decides whether it is normalized.
+///
+/// A `continuous` distribution is given by the name of its struct, and the variable is promoted to
+/// the type of its parameters. A `discrete` one is given by the name of its macro, whose `@new` arm
+/// builds the distribution from the parameters, and the variable and bounds are cast to `i64`.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! univariate {
+    (@new continuous $dist: ident; $var: expr; $a: expr) => {{
+        let (x, a) = $crate::promote!($var, $a);
+        (x, $crate::distributions::$dist::new(a))
+    }};
+    (@new continuous $dist: ident; $var: expr; $a: expr, $b: expr) => {{
+        let (x, a, b) = $crate::promote!($var, $a, $b);
+        (x, $crate::distributions::$dist::new(a, b))
+    }};
+    (@new continuous $dist: ident; $var: expr; $a: expr, $b: expr, $c: expr) => {{
+        let (x, a, b, c) = $crate::promote!($var, $a, $b, $c);
+        (x, $crate::distributions::$dist::new(a, b, c))
+    }};
+    (@new discrete $dist: ident; $var: expr; $($param: expr),+) => {
+        ($var as i64, $crate::$dist!(@new $($param),+))
+    };
+    (@bound continuous $x: ident, $bound: expr) => {
+        $crate::functions::promote($x, $bound).1
+    };
+    (@bound discrete $x: ident, $bound: expr) => {
+        $bound as i64
+    };
+    ( $kind: ident $dist: ident, $method: ident; $var: expr; $($param: expr),+ ) => {{
+        let (x, d) = $crate::univariate!(@new $kind $dist; $var; $($param),+);
+        $crate::distributions::Distribution::$method(&d, x)
+    }};
+    (
+        $kind: ident $dist: ident, $method: ident;
+        $var: expr; $($param: expr),+; T[$($low: expr)?, $($high: expr)?]
+    ) => {{
+        let (x, d) = $crate::univariate!(@new $kind $dist; $var; $($param),+);
+        $crate::truncate!(
+            d,
+            x,
+            $method,
+            [$($crate::univariate!(@bound $kind x, $low))?],
+            [$($crate::univariate!(@bound $kind x, $high))?]
+        )
+    }};
+    ( $kind: ident $dist: ident, $method: ident; $var: expr; $($param: expr),+; left ) => {{
+        let (x, d) = $crate::univariate!(@new $kind $dist; $var; $($param),+);
+        $crate::distributions::Censoring::Left(x).log_probability(&d)
+    }};
+    ( $kind: ident $dist: ident, $method: ident; $var: expr; $($param: expr),+; right ) => {{
+        let (x, d) = $crate::univariate!(@new $kind $dist; $var; $($param),+);
+        $crate::distributions::Censoring::Right(x).log_probability(&d)
+    }};
+    (
+        $kind: ident $dist: ident, $method: ident;
+        $low: expr, $high: expr; $($param: expr),+; interval
+    ) => {{
+        let (x, d) = $crate::univariate!(@new $kind $dist; $low; $($param),+);
+        let high = $crate::univariate!(@bound $kind x, $high);
+        $crate::distributions::Censoring::Interval(x, high).log_probability(&d)
+    }};
+}
+
+/// Evaluates a statement of the vectorized macro `dist` separately at each observation, for the
+/// `@pointwise` arms used by `#[model(..., pointwise)]`, e.g. `normal_lpdf!(@pointwise y; mu,
+/// sigma)`. Truncated and censored statements are not vectorized, so they give a single value.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! pointwise {
+    ( $dist: ident; $var: expr; $a: expr ) => {
+        $crate::distributions::vectorized::pointwise(
+            [
+                $crate::distributions::vectorized::Broadcast::column(&$var),
+                $crate::distributions::vectorized::Broadcast::column(&$a),
+            ],
+            |[var, a]| $crate::$dist!(var; a),
+        )
+    };
+    ( $dist: ident; $var: expr; $a: expr, $b: expr ) => {
+        $crate::distributions::vectorized::pointwise(
+            [
+                $crate::distributions::vectorized::Broadcast::column(&$var),
+                $crate::distributions::vectorized::Broadcast::column(&$a),
+                $crate::distributions::vectorized::Broadcast::column(&$b),
+            ],
+            |[var, a, b]| $crate::$dist!(var; a, b),
+        )
+    };
+    ( $dist: ident; $($args: tt)+ ) => {
+        vec![$crate::functions::Real::value(&$crate::$dist!($($args)+))]
+    };
+}
+
+#[macro_export]
+macro_rules! normal {
+    ( $var: expr; $mean: expr, $sigma: expr ) => {
+        $crate::distributions::vectorized::normal(&$var, &$mean, &$sigma, false)
+    };
+    ( $($args: tt)+ ) => {
+        $crate::univariate!(continuous Normal, unnormalized_log_density; $($args)+)
+    };
+}
+
+#[macro_export]
+macro_rules! normal_lpdf {
+    (@pointwise $($args: tt)+) => {
+        $crate::pointwise!(normal_lpdf; $($args)+)
+    };
+    ( $var: expr; $mean: expr, $sigma: expr ) => {
+        $crate::distributions::vectorized::normal(&$var, &$mean, &$sigma, true)
+    };
+    ( $($args: tt)+ ) => {
+        $crate::univariate!(continuous Normal, log_density; $($args)+)
+    };
+}
+
+#[macro_export]
+macro_rules! normal_rng {
+    ( $mean: expr, $sigma: expr ) => {{
+        let (mean, sigma) = $crate::promote!($mean, $sigma);
+        let d = $crate::distributions::Normal::new(mean, sigma);
+        $crate::distributions::Distribution::sample(&d)
+    }};
+}
+
+#[macro_export]
+macro_rules! exponential {
+    ( $var: expr; $rate: expr ) => {
+        $crate::distributions::vectorized::exponential(&$var, &$rate)
+    };
+    ( $($args: tt)+ ) => {
+        $crate::univariate!(continuous Exponential, unnormalized_log_density; $($args)+)
+    };
+}
+
+#[macro_export]
+macro_rules! exponential_lpdf {
+    (@pointwise $($args: tt)+) => {
+        $crate::pointwise!(exponential_lpdf; $($args)+)
+    };
+    ( $var: expr; $rate: expr ) => {
+        $crate::distributions::vectorized::exponential(&$var, &$rate)
+    };
+    ( $($args: tt)+ ) => {
+        $crate::univariate!(continuous Exponential, log_density; $($args)+)
+    };
+}
+
+#[macro_export]
+macro_rules! exponential_rng {
+    ( $rate: expr ) => {{
+        let d = $crate::distributions::Exponential::new($rate);
+        $crate::distributions::Distribution::sample(&d)
+    }};
+}
+
+#[macro_export]
+macro_rules! uniform {
+    ( $($args: tt)+ ) => {
+        $crate::univariate!(continuous Uniform, unnormalized_log_density; $($args)+)
+    };
+}
+
+#[macro_export]
+macro_rules! uniform_lpdf {
+    ( $($args: tt)+ ) => {
+        $crate::univariate!(continuous Uniform, log_density; $($args)+)
+    };
+}
+
+#[macro_export]
+macro_rules! uniform_rng {
+    ( $lower: expr, $upper: expr ) => {{
+        let (lower, upper) = $crate::promote!($lower, $upper);
+        let d = $crate::distributions::Uniform::new(lower, upper);
+        $crate::distributions::Distribution::sample(&d)
+    }};
+}
+
+#[macro_export]
+macro_rules! gamma {
+    ( $($args: tt)+ ) => {
+        $crate::univariate!(continuous Gamma, unnormalized_log_density; $($args)+)
+    };
+}
+
+#[macro_export]
+macro_rules! gamma_lpdf {
+    ( $($args: tt)+ ) => {
+        $crate::univariate!(continuous Gamma, log_density; $($args)+)
+    };
+}
+
+#[macro_export]
+macro_rules! gamma_rng {
+    ( $alpha: expr, $beta: expr ) => {{
+        let (alpha, beta) = $crate::promote!($alpha, $beta);
+        let d = $crate::distributions::Gamma::new(alpha, beta);
+        $crate::distributions::Distribution::sample(&d)
+    }};
+}
+
+#[macro_export]
+macro_rules! laplace {
+    ( $var: expr; $mu: expr, $b: expr ) => {
+        $crate::distributions::vectorized::laplace(&$var, &$mu, &$b, false)
+    };
+    ( $($args: tt)+ ) => {
+        $crate::univariate!(continuous Laplace, unnormalized_log_density; $($args)+)
+    };
+}
+
+#[macro_export]
+macro_rules! laplace_lpdf {
+    (@pointwise $($args: tt)+) => {
+        $crate::pointwise!(laplace_lpdf; $($args)+)
+    };
+    ( $var: expr; $mu: expr, $b: expr ) => {
+        $crate::distributions::vectorized::laplace(&$var, &$mu, &$b, true)
+    };
+    ( $($args: tt)+ ) => {
+        $crate::univariate!(continuous Laplace, log_density; $($args)+)
+    };
+}
+
+#[macro_export]
+macro_rules! laplace_rng {
+    ( $mu: expr, $b: expr ) => {{
+        let (mu, b) = $crate::promote!($mu, $b);
+        let d = $crate::distributions::Laplace::new(mu, b);
+        $crate::distributions::Distribution::sample(&d)
+    }};
+}
+
+#[macro_export]
+macro_rules! beta {
+    ( $($args: tt)+ ) => {
+        $crate::univariate!(continuous Beta, unnormalized_log_density; $($args)+)
+    };
+}
+
+#[macro_export]
+macro_rules! beta_lpdf {
+    ( $($args: tt)+ ) => {
+        $crate::univariate!(continuous Beta, log_density; $($args)+)
+    };
+}
+
+#[macro_export]
+macro_rules! beta_rng {
+    ( $alpha: expr, $beta: expr ) => {{
+        let (alpha, beta) = $crate::promote!($alpha, $beta);
+        let d = $crate::distributions::Beta::new(alpha, beta);
+        $crate::distributions::Distribution::sample(&d)
+    }};
+}
+
+#[macro_export]
+macro_rules! bernoulli {
+    ( $var: expr; $theta: expr ) => {
+        $crate::distributions::vectorized::bernoulli(&$var, &$theta)
+    };
+    (@new $theta: expr) => {
+        $crate::distributions::Bernoulli::new($theta)
+    };
+    ( $($args: tt)+ ) => {
+        $crate::univariate!(discrete bernoulli, unnormalized_log_density; $($args)+)
+    };
+}
+
+#[macro_export]
+macro_rules! bernoulli_lpmf {
+    (@pointwise $($args: tt)+) => {
+        $crate::pointwise!(bernoulli_lpmf; $($args)+)
+    };
+    ( $var: expr; $theta: expr ) => {
+        $crate::distributions::vectorized::bernoulli(&$var, &$theta)
+    };
+    ( $($args: tt)+ ) => {
+        $crate::univariate!(discrete bernoulli, log_density; $($args)+)
+    };
+}
+
+#[macro_export]
+macro_rules! bernoulli_rng {
+    ( $theta: expr ) => {{
+        let d = $crate::distributions::Bernoulli::new($theta);
+        $crate::distributions::Distribution::sample(&d)
+    }};
+}
+
+#[macro_export]
+macro_rules! binomial {
+    ( $n: expr; $N: expr, $theta: expr ) => {
+        $crate::distributions::vectorized::binomial(&$n, &$N, &$theta, false)
+    };
+    (@new $N: expr, $theta: expr) => {
+        $crate::distributions::Binomial::new($N as i64, $theta)
+    };
+    ( $($args: tt)+ ) => {
+        $crate::univariate!(discrete binomial, unnormalized_log_density; $($args)+)
+    };
+}
+
+#[macro_export]
+macro_rules! binomial_lpmf {
+    (@pointwise $($args: tt)+) => {
+        $crate::pointwise!(binomial_lpmf; $($args)+)
+    };
+    ( $n: expr; $N: expr, $theta: expr ) => {
+        $crate::distributions::vectorized::binomial(&$n, &$N, &$theta, true)
+    };
+    ( $($args: tt)+ ) => {
+        $crate::univariate!(discrete binomial, log_density; $($args)+)
+    };
+}
+
+#[macro_export]
+macro_rules! binomial_rng {
+    ( $N: expr, $theta: expr ) => {{
+        let d = $crate::distributions::Binomial::new($N as i64, $theta);
+        $crate::distributions::Distribution::sample(&d)
+    }};
+}
+
+#[macro_export]
+macro_rules! poisson {
+    ( $n: expr; $lambda: expr ) => {
+        $crate::distributions::vectorized::poisson(&$n, &$lambda, false)
+    };
+    (@new $lambda: expr) => {
+        $crate::distributions::Poisson::new($lambda)
+    };
+    ( $($args: tt)+ ) => {
+        $crate::univariate!(discrete poisson, unnormalized_log_density; $($args)+)
+    };
+}
+
+#[macro_export]
+macro_rules! poisson_lpmf {
+    (@pointwise $($args: tt)+) => {
+        $crate::pointwise!(poisson_lpmf; $($args)+)
+    };
+    ( $n: expr; $lambda: expr ) => {
+        $crate::distributions::vectorized::poisson(&$n, &$lambda, true)
+    };
+    ( $($args: tt)+ ) => {
+        $crate::univariate!(discrete poisson, log_density; $($args)+)
+    };
+}
+
+#[macro_export]
+macro_rules! poisson_rng {
+    ( $lambda: expr ) => {{
+        let d = $crate::distributions::Poisson::new($lambda);
+        $crate::distributions::Distribution::sample(&d)
+    }};
+}
+
+#[macro_export]
+macro_rules! neg_binomial {
+    (@new $mu: expr, $phi: expr) => {{
+        let (mu, phi) = $crate::promote!($mu, $phi);
+        $crate::distributions::NegativeBinomial::new(mu, phi)
+    }};
+    ( $($args: tt)+ ) => {
+        $crate::univariate!(discrete neg_binomial, unnormalized_log_density; $($args)+)
+    };
+}
+
+#[macro_export]
+macro_rules! neg_binomial_lpmf {
+    ( $($args: tt)+ ) => {
+        $crate::univariate!(discrete neg_binomial, log_density; $($args)+)
+    };
+}
+
+#[macro_export]
+macro_rules! neg_binomial_rng {
+    ( $mu: expr, $phi: expr ) => {{
+        let (mu, phi) = $crate::promote!($mu, $phi);
+        let d = $crate::distributions::NegativeBinomial::new(mu, phi);
+        $crate::distributions::Distribution::sample(&d)
+    }};
+}
+
+#[macro_export]
+macro_rules! beta_binomial {
+    (@new $N: expr, $alpha: expr, $beta: expr) => {{
+        let (alpha, beta) = $crate::promote!($alpha, $beta);
+        $crate::distributions::BetaBinomial::new($N as i64, alpha, beta)
+    }};
+    ( $($args: tt)+ ) => {
+        $crate::univariate!(discrete beta_binomial, unnormalized_log_density; $($args)+)
+    };
+}
+
+#[macro_export]
+macro_rules! beta_binomial_lpmf {
+    ( $($args: tt)+ ) => {
+        $crate::univariate!(discrete beta_binomial, log_density; $($args)+)
+    };
+}
+
+#[macro_export]
+macro_rules! beta_binomial_rng {
+    ( $N: expr, $alpha: expr, $beta: expr ) => {{
+        let (alpha, beta) = $crate::promote!($alpha, $beta);
+        let d = $crate::distributions::BetaBinomial::new($N as i64, alpha, beta);
+        $crate::distributions::Distribution::sample(&d)
+    }};
+}
+
+#[macro_export]
+macro_rules! geometric {
+    (@new $theta: expr) => {
+        $crate::distributions::Geometric::new($theta)
+    };
+    ( $($args: tt)+ ) => {
+        $crate::univariate!(discrete geometric, unnormalized_log_density; $($args)+)
+    };
+}
+
+#[macro_export]
+macro_rules! geometric_lpmf {
+    ( $($args: tt)+ ) => {
+        $crate::univariate!(discrete geometric, log_density; $($args)+)
+    };
+}
+
+#[macro_export]
+macro_rules! geometric_rng {
+    ( $theta: expr ) => {{
+        let d = $crate::distributions::Geometric::new($theta);
+        $crate::distributions::Distribution::sample(&d)
+    }};
+}
+
+#[macro_export]
+macro_rules! hypergeometric {
+    (@new $N: expr, $a: expr, $b: expr) => {
+        $crate::distributions::Hypergeometric::new($N as i64, $a as i64, $b as i64)
+    };
+    ( $($args: tt)+ ) => {
+        $crate::univariate!(discrete hypergeometric, log_density; $($args)+)
+    };
+}
+
+#[macro_export]
+macro_rules! hypergeometric_lpmf {
+    ( $($args: tt)+ ) => {
+        $crate::univariate!(discrete hypergeometric, log_density; $($args)+)
+    };
+}
+
+#[macro_export]
+macro_rules! hypergeometric_rng {
+    ( $N: expr, $a: expr, $b: expr ) => {{
+        let d = $crate::distributions::Hypergeometric::new($N as i64, $a as i64, $b as i64);
+        $crate::distributions::Distribution::sample(&d)
+    }};
+}
+
+#[macro_export]
+macro_rules! zero_inflated_poisson {
+    (@new $theta: expr, $lambda: expr) => {{
+        let (theta, lambda) = $crate::promote!($theta, $lambda);
+        $crate::distributions::ZeroInflatedPoisson::new(theta, lambda)
+    }};
+    ( $($args: tt)+ ) => {
+        $crate::univariate!(discrete zero_inflated_poisson, unnormalized_log_density; $($args)+)
+    };
+}
+
+#[macro_export]
+macro_rules! zero_inflated_poisson_lpmf {
+    ( $($args: tt)+ ) => {
+        $crate::univariate!(discrete zero_inflated_poisson, log_density; $($args)+)
+    };
+}
+
+#[macro_export]
+macro_rules! zero_inflated_poisson_rng {
+    ( $theta: expr, $lambda: expr ) => {{
+        let (theta, lambda) = $crate::promote!($theta, $lambda);
+        let d = $crate::distributions::ZeroInflatedPoisson::new(theta, lambda);
+        $crate::distributions::Distribution::sample(&d)
+    }};
+}
+
+#[macro_export]
+macro_rules! zero_inflated_neg_binomial {
+    (@new $theta: expr, $mu: expr, $phi: expr) => {{
+        let (theta, mu, phi) = $crate::promote!($theta, $mu, $phi);
+        $crate::distributions::ZeroInflatedNegativeBinomial::new(theta, mu, phi)
+    }};
+    ( $($args: tt)+ ) => {
+        $crate::univariate!(
+            discrete zero_inflated_neg_binomial, unnormalized_log_density; $($args)+
+        )
+    };
+}
+
+#[macro_export]
+macro_rules! zero_inflated_neg_binomial_lpmf {
+    ( $($args: tt)+ ) => {
+        $crate::univariate!(discrete zero_inflated_neg_binomial, log_density; $($args)+)
+    };
+}
+
+#[macro_export]
+macro_rules! zero_inflated_neg_binomial_rng {
+    ( $theta: expr, $mu: expr, $phi: expr ) => {{
+        let (theta, mu, phi) = $crate::promote!($theta, $mu, $phi);
+        let d = $crate::distributions::ZeroInflatedNegativeBinomial::new(theta, mu, phi);
+        $crate::distributions::Distribution::sample(&d)
+    }};
+}
+
+#[macro_export]
+macro_rules! hurdle_poisson {
+    (@new $theta: expr, $lambda: expr) => {{
+        let (theta, lambda) = $crate::promote!($theta, $lambda);
+        $crate::distributions::HurdlePoisson::new(theta, lambda)
+    }};
+    ( $($args: tt)+ ) => {
+        $crate::univariate!(discrete hurdle_poisson, unnormalized_log_density; $($args)+)
+    };
+}
+
+#[macro_export]
+macro_rules! hurdle_poisson_lpmf {
+    ( $($args: tt)+ ) => {
+        $crate::univariate!(discrete hurdle_poisson, log_density; $($args)+)
+    };
+}
+
+#[macro_export]
+macro_rules! hurdle_poisson_rng {
+    ( $theta: expr, $lambda: expr ) => {{
+        let (theta, lambda) = $crate::promote!($theta, $lambda);
+        let d = $crate::distributions::HurdlePoisson::new(theta, lambda);
+        $crate::distributions::Distribution::sample(&d)
+    }};
+}
+
+#[macro_export]
+macro_rules! hurdle_neg_binomial {
+    (@new $theta: expr, $mu: expr, $phi: expr) => {{
+        let (theta, mu, phi) = $crate::promote!($theta, $mu, $phi);
+        $crate::distributions::HurdleNegativeBinomial::new(theta, mu, phi)
+    }};
+    ( $($args: tt)+ ) => {
+        $crate::univariate!(discrete hurdle_neg_binomial, unnormalized_log_density; $($args)+)
+    };
+}
+
+#[macro_export]
+macro_rules! hurdle_neg_binomial_lpmf {
+    ( $($args: tt)+ ) => {
+        $crate::univariate!(discrete hurdle_neg_binomial, log_density; $($args)+)
+    };
+}
+
+#[macro_export]
+macro_rules! hurdle_neg_binomial_rng {
+    ( $theta: expr, $mu: expr, $phi: expr ) => {{
+        let (theta, mu, phi) = $crate::promote!($theta, $mu, $phi);
+        let d = $crate::distributions::HurdleNegativeBinomial::new(theta, mu, phi);
+        $crate::distributions::Distribution::sample(&d)
+    }};
+}
+
+#[macro_export]
+macro_rules! categorical {
+    ( $k: expr; $theta: expr ) => {{
+        let d = $crate::distributions::Categorical::new(&$theta[..]);
+        $crate::distributions::Distribution::unnormalized_log_density(&d, $k as i64)
+    }};
+}
+
+#[macro_export]
+macro_rules! categorical_lpmf {
+    ( $k: expr; $theta: expr ) => {{
+        let d = $crate::distributions::Categorical::new(&$theta[..]);
+        $crate::distributions::Distribution::log_density(&d, $k as i64)
+    }};
+}
+
+#[macro_export]
+macro_rules! categorical_rng {
+    ( $theta: expr ) => {{
+        let d = $crate::distributions::Categorical::new(&$theta[..]);
+        $crate::distributions::Distribution::sample(&d)
+    }};
+}
+
+#[macro_export]
+macro_rules! categorical_logit {
+    ( $k: expr; $eta: expr ) => {{
+        let d = $crate::distributions::Categorical::with_logits(&$eta[..]);
+        $crate::distributions::Distribution::unnormalized_log_density(&d, $k as i64)
+    }};
+}
+
+#[macro_export]
+macro_rules! categorical_logit_lpmf {
+    ( $k: expr; $eta: expr ) => {{
+        let d = $crate::distributions::Categorical::with_logits(&$eta[..]);
+        $crate::distributions::Distribution::log_density(&d, $k as i64)
+    }};
+}
+
+#[macro_export]
+macro_rules! categorical_logit_rng {
+    ( $eta: expr ) => {{
+        let d = $crate::distributions::Categorical::with_logits(&$eta[..]);
+        $crate::distributions::Distribution::sample(&d)
+    }};
+}
+
+#[macro_export]
+macro_rules! ordered_logistic {
+    ( $k: expr; $eta: expr, $c: expr ) => {{
+        let (eta, c) = $crate::functions::promote_slices(&[$eta], &$c[..]);
+        let d = $crate::distributions::OrderedLogistic::new(eta[0], &c);
+        $crate::distributions::Distribution::unnormalized_log_density(&d, $k as i64)
+    }};
+}
+
+#[macro_export]
+macro_rules! ordered_logistic_lpmf {
+    ( $k: expr; $eta: expr, $c: expr ) => {{
+        let (eta, c) = $crate::functions::promote_slices(&[$eta], &$c[..]);
+        let d = $crate::distributions::OrderedLogistic::new(eta[0], &c);
+        $crate::distributions::Distribution::log_density(&d, $k as i64)
+    }};
+}
+
+#[macro_export]
+macro_rules! ordered_logistic_rng {
+    ( $eta: expr, $c: expr ) => {{
+        let (eta, c) = $crate::functions::promote_slices(&[$eta], &$c[..]);
+        let d = $crate::distributions::OrderedLogistic::new(eta[0], &c);
+        $crate::distributions::Distribution::sample(&d)
+    }};
+}
+
+#[macro_export]
+macro_rules! ordered_probit {
+    ( $k: expr; $eta: expr, $c: expr ) => {{
+        let (eta, c) = $crate::functions::promote_slices(&[$eta], &$c[..]);
+        let d = $crate::distributions::OrderedProbit::new(eta[0], &c);
+        $crate::distributions::Distribution::unnormalized_log_density(&d, $k as i64)
+    }};
+}
+
+#[macro_export]
+macro_rules! ordered_probit_lpmf {
+    ( $k: expr; $eta: expr, $c: expr ) => {{
+        let (eta, c) = $crate::functions::promote_slices(&[$eta], &$c[..]);
+        let d = $crate::distributions::OrderedProbit::new(eta[0], &c);
+        $crate::distributions::Distribution::log_density(&d, $k as i64)
+    }};
+}
+
+#[macro_export]
+macro_rules! ordered_probit_rng {
+    ( $eta: expr, $c: expr ) => {{
+        let (eta, c) = $crate::functions::promote_slices(&[$eta], &$c[..]);
+        let d = $crate::distributions::OrderedProbit::new(eta[0], &c);
+        $crate::distributions::Distribution::sample(&d)
+    }};
+}
+
+#[macro_export]
+macro_rules! cauchy {
+    ( $y: expr; $mu: expr, $sigma: expr ) => {
+        $crate::distributions::vectorized::cauchy(&$y, &$mu, &$sigma, false)
+    };
+    ( $($args: tt)+ ) => {
+        $crate::univariate!(continuous Cauchy, unnormalized_log_density; $($args)+)
+    };
+}
+
+#[macro_export]
+macro_rules! cauchy_lpdf {
+    (@pointwise $($args: tt)+) => {
+        $crate::pointwise!(cauchy_lpdf; $($args)+)
+    };
+    ( $y: expr; $mu: expr, $sigma: expr ) => {
+        $crate::distributions::vectorized::cauchy(&$y, &$mu, &$sigma, true)
+    };
+    ( $($args: tt)+ ) => {
+        $crate::univariate!(continuous Cauchy, log_density; $($args)+)
+    };
+}
+
+#[macro_export]
+macro_rules! cauchy_rng {
+    ( $mu: expr, $sigma: expr ) => {{
+        let (mu, sigma) = $crate::promote!($mu, $sigma);
+        let d = $crate::distributions::Cauchy::new(mu, sigma);
+        $crate::distributions::Distribution::sample(&d)
+    }};
+}
+
+#[macro_export]
+macro_rules! lognormal {
+    ( $y: expr; $mu: expr, $sigma: expr ) => {
+        $crate::distributions::vectorized::lognormal(&$y, &$mu, &$sigma, false)
+    };
+    ( $($args: tt)+ ) => {
+        $crate::univariate!(continuous Lognormal, unnormalized_log_density; $($args)+)
+    };
+}
+
+#[macro_export]
+macro_rules! lognormal_lpdf {
+    (@pointwise $($args: tt)+) => {
+        $crate::pointwise!(lognormal_lpdf; $($args)+)
+    };
+    ( $y: expr; $mu: expr, $sigma: expr ) => {
+        $crate::distributions::vectorized::lognormal(&$y, &$mu, &$sigma, true)
+    };
+    ( $($args: tt)+ ) => {
+        $crate::univariate!(continuous Lognormal, log_density; $($args)+)
+    };
+}
+
+#[macro_export]
+macro_rules! lognormal_rng {
+    ( $mu: expr, $sigma: expr ) => {{
+        let (mu, sigma) = $crate::promote!($mu, $sigma);
+        let d = $crate::distributions::Lognormal::new(mu, sigma);
+        $crate::distributions::Distribution::sample(&d)
+    }};
+}
+
+#[macro_export]
+macro_rules! rayleigh {
+    ( $($args: tt)+ ) => {
+        $crate::univariate!(continuous Rayleigh, unnormalized_log_density; $($args)+)
+    };
+}
+
+#[macro_export]
+macro_rules! rayleigh_lpdf {
+    ( $($args: tt)+ ) => {
+        $crate::univariate!(continuous Rayleigh, log_density; $($args)+)
+    };
+}
+
+#[macro_export]
+macro_rules! rayleigh_rng {
+    ( $sigma: expr ) => {{
+        let d = $crate::distributions::Rayleigh::new($sigma);
+        $crate::distributions::Distribution::sample(&d)
+    }};
+}
+
+#[macro_export]
+macro_rules! pareto {
+    ( $($args: tt)+ ) => {
+        $crate::univariate!(continuous Pareto, unnormalized_log_density; $($args)+)
+    };
+}
+
+#[macro_export]
+macro_rules! pareto_lpdf {
+    ( $($args: tt)+ ) => {
+        $crate::univariate!(continuous Pareto, log_density; $($args)+)
+    };
+}
+
+#[macro_export]
+macro_rules! pareto_rng {
+    ( $ymin: expr, $alpha: expr ) => {{
+        let (ymin, alpha) = $crate::promote!($ymin, $alpha);
+        let d = $crate::distributions::Pareto::new(ymin, alpha);
+        $crate::distributions::Distribution::sample(&d)
+    }};
+}
+
+#[macro_export]
+macro_rules! student_t {
+    ( $($args: tt)+ ) => {
+        $crate::univariate!(continuous StudentT, unnormalized_log_density; $($args)+)
+    };
+}
+
+#[macro_export]
+macro_rules! student_t_lpdf {
+    ( $($args: tt)+ ) => {
+        $crate::univariate!(continuous StudentT, log_density; $($args)+)
+    };
+}
+
+#[macro_export]
+macro_rules! student_t_rng {
+    ( $nu: expr, $mu: expr, $sigma: expr ) => {{
+        let (nu, mu, sigma) = $crate::promote!($nu, $mu, $sigma);
+        let d = $crate::distributions::StudentT::new(nu, mu, sigma);
+        $crate::distributions::Distribution::sample(&d)
+    }};
+}
+
+#[macro_export]
+macro_rules! half_normal {
+    ( $($args: tt)+ ) => {
+        $crate::univariate!(continuous HalfNormal, unnormalized_log_density; $($args)+)
+    };
+}
+
+#[macro_export]
+macro_rules! half_normal_lpdf {
+    ( $($args: tt)+ ) => {
+        $crate::univariate!(continuous HalfNormal, log_density; $($args)+)
+    };
+}
+
+#[macro_export]
+macro_rules! half_normal_rng {
+    ( $sigma: expr ) => {{
+        let d = $crate::distributions::HalfNormal::new($sigma);
+        $crate::distributions::Distribution::sample(&d)
+    }};
+}
+
+#[macro_export]
+macro_rules! half_cauchy {
+    ( $($args: tt)+ ) => {
+        $crate::univariate!(continuous HalfCauchy, unnormalized_log_density; $($args)+)
+    };
+}
+
+#[macro_export]
+macro_rules! half_cauchy_lpdf {
+    ( $($args: tt)+ ) => {
+        $crate::univariate!(continuous HalfCauchy, log_density; $($args)+)
+    };
+}
+
+#[macro_export]
+macro_rules! half_cauchy_rng {
+    ( $sigma: expr ) => {{
+        let d = $crate::distributions::HalfCauchy::new($sigma);
+        $crate::distributions::Distribution::sample(&d)
+    }};
+}
+
+#[macro_export]
+macro_rules! half_student_t {
+    ( $y: expr; $nu: expr, $sigma: expr ) => {{
+        let (x, nu, sigma) = $crate::promote!($y, $nu, $sigma);
+        let d = $crate::distributions::HalfStudentT::new(nu, sigma);
+        $crate::distributions::Distribution::unnormalized_log_density(&d, x)
+    }};
+}
+
+#[macro_export]
+macro_rules! half_student_t_lpdf {
+    ( $y: expr; $nu: expr, $sigma: expr ) => {{
+        let (x, nu, sigma) = $crate::promote!($y, $nu, $sigma);
+        let d = $crate::distributions::HalfStudentT::new(nu, sigma);
+        $crate::distributions::Distribution::log_density(&d, x)
+    }};
+}
+
+#[macro_export]
+macro_rules! half_student_t_rng {
+    ( $nu: expr, $sigma: expr ) => {{
+        let (nu, sigma) = $crate::promote!($nu, $sigma);
+        let d = $crate::distributions::HalfStudentT::new(nu, sigma);
+        $crate::distributions::Distribution::sample(&d)
+    }};
+}
+
+#[macro_export]
+macro_rules! inv_gamma {
+    ( $y: expr; $alpha: expr, $beta: expr ) => {{
+        let (x, alpha, beta) = $crate::promote!($y, $alpha, $beta);
+        let d = $crate::distributions::InverseGamma::new(alpha, beta);
+        $crate::distributions::Distribution::unnormalized_log_density(&d, x)
+    }};
+}
+
+#[macro_export]
+macro_rules! inv_gamma_lpdf {
+    ( $y: expr; $alpha: expr, $beta: expr ) => {{
+        let (x, alpha, beta) = $crate::promote!($y, $alpha, $beta);
+        let d = $crate::distributions::InverseGamma::new(alpha, beta);
+        $crate::distributions::Distribution::log_density(&d, x)
+    }};
+}
+
+#[macro_export]
+macro_rules! inv_gamma_rng {
+    ( $alpha: expr, $beta: expr ) => {{
+        let (alpha, beta) = $crate::promote!($alpha, $beta);
+        let d = $crate::distributions::InverseGamma::new(alpha, beta);
+        $crate::distributions::Distribution::sample(&d)
+    }};
+}
+
+#[macro_export]
+macro_rules! weibull {
+    ( $($args: tt)+ ) => {
+        $crate::univariate!(continuous Weibull, unnormalized_log_density; $($args)+)
+    };
+}
+
+#[macro_export]
+macro_rules! weibull_lpdf {
+    ( $($args: tt)+ ) => {
+        $crate::univariate!(continuous Weibull, log_density; $($args)+)
+    };
+}
+
+#[macro_export]
+macro_rules! weibull_rng {
+    ( $k: expr, $lambda: expr ) => {{
+        let (k, lambda) = $crate::promote!($k, $lambda);
+        let d = $crate::distributions::Weibull::new(k, lambda);
+        $crate::distributions::Distribution::sample(&d)
+    }};
+}
+
+#[macro_export]
+macro_rules! gumbel {
+    ( $($args: tt)+ ) => {
+        $crate::univariate!(continuous Gumbel, unnormalized_log_density; $($args)+)
+    };
+}
+
+#[macro_export]
+macro_rules! gumbel_lpdf {
+    ( $($args: tt)+ ) => {
+        $crate::univariate!(continuous Gumbel, log_density; $($args)+)
+    };
+}
+
+#[macro_export]
+macro_rules! gumbel_rng {
+    ( $mu: expr, $beta: expr ) => {{
+        let (mu, beta) = $crate::promote!($mu, $beta);
+        let d = $crate::distributions::Gumbel::new(mu, beta);
+        $crate::distributions::Distribution::sample(&d)
+    }};
+}
+
+#[macro_export]
+macro_rules! logistic {
+    ( $($args: tt)+ ) => {
+        $crate::univariate!(continuous Logistic, unnormalized_log_density; $($args)+)
+    };
+}
+
+#[macro_export]
+macro_rules! logistic_lpdf {
+    ( $($args: tt)+ ) => {
+        $crate::univariate!(continuous Logistic, log_density; $($args)+)
+    };
+}
+
+#[macro_export]
+macro_rules! logistic_rng {
+    ( $mu: expr, $s: expr ) => {{
+        let (mu, s) = $crate::promote!($mu, $s);
+        let d = $crate::distributions::Logistic::new(mu, s);
+        $crate::distributions::Distribution::sample(&d)
+    }};
+}
+
+#[macro_export]
+macro_rules! skew_normal {
+    ( $y: expr; $xi: expr, $omega: expr, $alpha: expr ) => {{
+        let (x, xi, omega, alpha) = $crate::promote!($y, $xi, $omega, $alpha);
+        let d = $crate::distributions::SkewNormal::new(xi, omega, alpha);
+        $crate::distributions::Distribution::unnormalized_log_density(&d, x)
+    }};
+}
+
+#[macro_export]
+macro_rules! skew_normal_lpdf {
+    ( $y: expr; $xi: expr, $omega: expr, $alpha: expr ) => {{
+        let (x, xi, omega, alpha) = $crate::promote!($y, $xi, $omega, $alpha);
+        let d = $crate::distributions::SkewNormal::new(xi, omega, alpha);
+        $crate::distributions::Distribution::log_density(&d, x)
+    }};
+}
+
+#[macro_export]
+macro_rules! skew_normal_rng {
+    ( $xi: expr, $omega: expr, $alpha: expr ) => {{
+        let (xi, omega, alpha) = $crate::promote!($xi, $omega, $alpha);
+        let d = $crate::distributions::SkewNormal::new(xi, omega, alpha);
+        $crate::distributions::Distribution::sample(&d)
+    }};
+}
+
+#[macro_export]
+macro_rules! exp_mod_normal {
+    ( $($args: tt)+ ) => {
+        $crate::univariate!(continuous ExpModNormal, unnormalized_log_density; $($args)+)
+    };
+}
+
+#[macro_export]
+macro_rules! exp_mod_normal_lpdf {
+    ( $($args: tt)+ ) => {
+        $crate::univariate!(continuous ExpModNormal, log_density; $($args)+)
+    };
+}
+
+#[macro_export]
+macro_rules! exp_mod_normal_rng {
+    ( $mu: expr, $sigma: expr, $lambda: expr ) => {{
+        let (mu, sigma, lambda) = $crate::promote!($mu, $sigma, $lambda);
+        let d = $crate::distributions::ExpModNormal::new(mu, sigma, lambda);
+        $crate::distributions::Distribution::sample(&d)
+    }};
+}
+
+#[macro_export]
+macro_rules! von_mises {
+    ( $y: expr; $mu: expr, $kappa: expr ) => {{
+        let (x, mu, kappa) = $crate::promote!($y, $mu, $kappa);
+        let d = $crate::distributions::VonMises::new(mu, kappa);
+        $crate::distributions::Distribution::unnormalized_log_density(&d, x)
+    }};
+}
+
+#[macro_export]
+macro_rules! von_mises_lpdf {
+    ( $y: expr; $mu: expr, $kappa: expr ) => {{
+        let (x, mu, kappa) = $crate::promote!($y, $mu, $kappa);
+        let d = $crate::distributions::VonMises::new(mu, kappa);
+        $crate::distributions::Distribution::log_density(&d, x)
+    }};
+}
+
+#[macro_export]
+macro_rules! von_mises_rng {
+    ( $mu: expr, $kappa: expr ) => {{
+        let (mu, kappa) = $crate::promote!($mu, $kappa);
+        let d = $crate::distributions::VonMises::new(mu, kappa);
+        $crate::distributions::Distribution::sample(&d)
+    }};
+}
+
+#[macro_export]
+macro_rules! wrapped_normal {
+    ( $y: expr; $mu: expr, $sigma: expr ) => {{
+        let (x, mu, sigma) = $crate::promote!($y, $mu, $sigma);
+        let d = $crate::distributions::WrappedNormal::new(mu, sigma);
+        $crate::distributions::Distribution::unnormalized_log_density(&d, x)
+    }};
+}
+
+#[macro_export]
+macro_rules! wrapped_normal_lpdf {
+    ( $y: expr; $mu: expr, $sigma: expr ) => {{
+        let (x, mu, sigma) = $crate::promote!($y, $mu, $sigma);
+        let d = $crate::distributions::WrappedNormal::new(mu, sigma);
+        $crate::distributions::Distribution::log_density(&d, x)
+    }};
+}
+
+#[macro_export]
+macro_rules! wrapped_normal_rng {
+    ( $mu: expr, $sigma: expr ) => {{
+        let (mu, sigma) = $crate::promote!($mu, $sigma);
+        let d = $crate::distributions::WrappedNormal::new(mu, sigma);
+        $crate::distributions::Distribution::sample(&d)
+    }};
+}
+
+#[macro_export]
+macro_rules! wrapped_cauchy {
+    ( $y: expr; $mu: expr, $sigma: expr ) => {{
+        let (x, mu, sigma) = $crate::promote!($y, $mu, $sigma);
+        let d = $crate::distributions::WrappedCauchy::new(mu, sigma);
+        $crate::distributions::Distribution::unnormalized_log_density(&d, x)
+    }};
+}
+
+#[macro_export]
+macro_rules! wrapped_cauchy_lpdf {
+    ( $y: expr; $mu: expr, $sigma: expr ) => {{
+        let (x, mu, sigma) = $crate::promote!($y, $mu, $sigma);
+        let d = $crate::distributions::WrappedCauchy::new(mu, sigma);
+        $crate::distributions::Distribution::log_density(&d, x)
+    }};
+}
+
+#[macro_export]
+macro_rules! wrapped_cauchy_rng {
+    ( $mu: expr, $sigma: expr ) => {{
+        let (mu, sigma) = $crate::promote!($mu, $sigma);
+        let d = $crate::distributions::WrappedCauchy::new(mu, sigma);
+        $crate::distributions::Distribution::sample(&d)
+    }};
+}
+
+#[macro_export]
+macro_rules! multi_normal {
+    ( $var: expr; $mu: expr, $sigma: expr ) => {{
+        let (x, mu, sigma) = $crate::promote_matrix!($var, $mu, $sigma);
+        let d = $crate::distributions::MultivariateNormal::new(&mu, &sigma);
+        $crate::distributions::Multivariate::unnormalized_log_density(&d, &x[..])
+    }};
+}
+
+#[macro_export]
+macro_rules! multi_normal_lpdf {
+    ( $var: expr; $mu: expr, $sigma: expr ) => {{
+        let (x, mu, sigma) = $crate::promote_matrix!($var, $mu, $sigma);
+        let d = $crate::distributions::MultivariateNormal::new(&mu, &sigma);
+        $crate::distributions::Multivariate::log_density(&d, &x[..])
+    }};
+}
+
+#[macro_export]
+macro_rules! multi_normal_rng {
+    ( $mu: expr, $sigma: expr ) => {{
+        let d = $crate::distributions::MultivariateNormal::new(&$mu[..], &$sigma[..]);
+        $crate::distributions::Multivariate::sample(&d)
+    }};
+}
+
+#[macro_export]
+macro_rules! multi_normal_prec {
+    ( $var: expr; $mu: expr, $omega: expr ) => {{
+        let (x, mu, omega) = $crate::promote_matrix!($var, $mu, $omega);
+        let d = $crate::distributions::MultivariateNormal::with_precision(&mu, &omega);
+        $crate::distributions::Multivariate::unnormalized_log_density(&d, &x[..])
+    }};
+}
+
+#[macro_export]
+macro_rules! multi_normal_prec_lpdf {
+    ( $var: expr; $mu: expr, $omega: expr ) => {{
+        let (x, mu, omega) = $crate::promote_matrix!($var, $mu, $omega);
+        let d = $crate::distributions::MultivariateNormal::with_precision(&mu, &omega);
+        $crate::distributions::Multivariate::log_density(&d, &x[..])
+    }};
+}
+
+#[macro_export]
+macro_rules! multi_normal_prec_rng {
+    ( $mu: expr, $omega: expr ) => {{
+        let d = $crate::distributions::MultivariateNormal::with_precision(&$mu[..], &$omega[..]);
+        $crate::distributions::Multivariate::sample(&d)
+    }};
+}
+
+#[macro_export]
+macro_rules! multi_normal_cholesky {
+    ( $var: expr; $mu: expr, $l: expr ) => {{
+        let (x, mu, l) = $crate::promote_matrix!($var, $mu, $l);
+        let d = $crate::distributions::MultivariateNormal::with_cholesky(&mu, &l);
+        $crate::distributions::Multivariate::unnormalized_log_density(&d, &x[..])
+    }};
+}
+
+#[macro_export]
+macro_rules! multi_normal_cholesky_lpdf {
+    ( $var: expr; $mu: expr, $l: expr ) => {{
+        let (x, mu, l) = $crate::promote_matrix!($var, $mu, $l);
+        let d = $crate::distributions::MultivariateNormal::with_cholesky(&mu, &l);
+        $crate::distributions::Multivariate::log_density(&d, &x[..])
+    }};
+}
+
+#[macro_export]
+macro_rules! multi_normal_cholesky_rng {
+    ( $mu: expr, $l: expr ) => {{
+        let d = $crate::distributions::MultivariateNormal::with_cholesky(&$mu[..], &$l[..]);
+        $crate::distributions::Multivariate::sample(&d)
+    }};
+}
+
+#[macro_export]
+macro_rules! multinomial {
+    ( $n: expr; $theta: expr ) => {{
+        let n = $n.iter().map(|&k| k as i64).collect::<Vec<_>>();
+        let d = $crate::distributions::Multinomial::new(&$theta[..]);
+        $crate::distributions::Multivariate::unnormalized_log_density(&d, &n[..])
+    }};
+}
+
+#[macro_export]
+macro_rules! multinomial_lpmf {
+    ( $n: expr; $theta: expr ) => {{
+        let n = $n.iter().map(|&k| k as i64).collect::<Vec<_>>();
+        let d = $crate::distributions::Multinomial::new(&$theta[..]);
+        $crate::distributions::Multivariate::log_density(&d, &n[..])
+    }};
+}
+
+#[macro_export]
+macro_rules! multinomial_rng {
+    ( $theta: expr, $n: expr ) => {{
+        let d = $crate::distributions::Multinomial::new(&$theta[..]);
+        d.sample_trials($n as i64)
+    }};
+}
+
+#[macro_export]
+macro_rules! dirichlet {
+    ( $var: expr; $alpha: expr ) => {{
+        let (x, alpha) = $crate::functions::promote_slices(&$var[..], &$alpha[..]);
+        let d = $crate::distributions::Dirichlet::new(&alpha);
+        $crate::distributions::Multivariate::unnormalized_log_density(&d, &x[..])
+    }};
+}
+
+#[macro_export]
+macro_rules! dirichlet_lpdf {
+    ( $var: expr; $alpha: expr ) => {{
+        let (x, alpha) = $crate::functions::promote_slices(&$var[..], &$alpha[..]);
+        let d = $crate::distributions::Dirichlet::new(&alpha);
+        $crate::distributions::Multivariate::log_density(&d, &x[..])
+    }};
+}
+
+#[macro_export]
+macro_rules! dirichlet_rng {
+    ( $alpha: expr ) => {{
+        let d = $crate::distributions::Dirichlet::new(&$alpha[..]);
+        $crate::distributions::Multivariate::sample(&d)
+    }};
+}
+
+#[macro_export]
+macro_rules! lkj_corr {
+    ( $var: expr; $eta: expr ) => {{
+        let (eta, x) = $crate::promote_square!($eta, $var);
+        let d = $crate::distributions::LkjCorr::new(eta, x.len());
+        $crate::distributions::Multivariate::unnormalized_log_density(&d, &x[..])
+    }};
+}
+
+#[macro_export]
+macro_rules! lkj_corr_lpdf {
+    ( $var: expr; $eta: expr ) => {{
+        let (eta, x) = $crate::promote_square!($eta, $var);
+        let d = $crate::distributions::LkjCorr::new(eta, x.len());
+        $crate::distributions::Multivariate::log_density(&d, &x[..])
+    }};
+}
+
+#[macro_export]
+macro_rules! lkj_corr_rng {
+    ( $eta: expr, $dims: expr ) => {{
+        let d = $crate::distributions::LkjCorr::new($eta, $dims);
+        $crate::distributions::Multivariate::sample(&d)
+    }};
+}
+
+#[macro_export]
+macro_rules! lkj_corr_cholesky {
+    ( $var: expr; $eta: expr ) => {{
+        let (eta, x) = $crate::promote_square!($eta, $var);
+        let d = $crate::distributions::LkjCorrCholesky::new(eta, x.len());
+        $crate::distributions::Multivariate::unnormalized_log_density(&d, &x[..])
+    }};
+}
+
+#[macro_export]
+macro_rules! lkj_corr_cholesky_lpdf {
+    ( $var: expr; $eta: expr ) => {{
+        let (eta, x) = $crate::promote_square!($eta, $var);
+        let d = $crate::distributions::LkjCorrCholesky::new(eta, x.len());
+        $crate::distributions::Multivariate::log_density(&d, &x[..])
+    }};
+}
+
+#[macro_export]
+macro_rules! lkj_corr_cholesky_rng {
+    ( $eta: expr, $dims: expr ) => {{
+        let d = $crate::distributions::LkjCorrCholesky::new($eta, $dims);
+        $crate::distributions::Multivariate::sample(&d)
+    }};
+}
+
+#[macro_export]
+macro_rules! wishart {
+    ( $var: expr; $nu: expr, $sigma: expr ) => {{
+        let (x, nu, sigma) = $crate::promote_square!($var, $nu, $sigma);
+        let d = $crate::distributions::Wishart::new(nu, &sigma);
+        $crate::distributions::Multivariate::unnormalized_log_density(&d, &x[..])
+    }};
+}
+
+#[macro_export]
+macro_rules! wishart_lpdf {
+    ( $var: expr; $nu: expr, $sigma: expr ) => {{
+        let (x, nu, sigma) = $crate::promote_square!($var, $nu, $sigma);
+        let d = $crate::distributions::Wishart::new(nu, &sigma);
+        $crate::distributions::Multivariate::log_density(&d, &x[..])
+    }};
+}
+
+#[macro_export]
+macro_rules! wishart_rng {
+    ( $nu: expr, $sigma: expr ) => {{
+        let (nu, sigma) = $crate::promote_square!($nu, $sigma);
+        let d = $crate::distributions::Wishart::new(nu, &sigma);
+        $crate::distributions::Multivariate::sample(&d)
+    }};
+}
+
+#[macro_export]
+macro_rules! inv_wishart {
+    ( $var: expr; $nu: expr, $psi: expr ) => {{
+        let (x, nu, psi) = $crate::promote_square!($var, $nu, $psi);
+        let d = $crate::distributions::InverseWishart::new(nu, &psi);
+        $crate::distributions::Multivariate::unnormalized_log_density(&d, &x[..])
+    }};
+}
+
+#[macro_export]
+macro_rules! inv_wishart_lpdf {
+    ( $var: expr; $nu: expr, $psi: expr ) => {{
+        let (x, nu, psi) = $crate::promote_square!($var, $nu, $psi);
+        let d = $crate::distributions::InverseWishart::new(nu, &psi);
+        $crate::distributions::Multivariate::log_density(&d, &x[..])
+    }};
+}
+
+#[macro_export]
+macro_rules! inv_wishart_rng {
+    ( $nu: expr, $psi: expr ) => {{
+        let (nu, psi) = $crate::promote_square!($nu, $psi);
+        let d = $crate::distributions::InverseWishart::new(nu, &psi);
+        $crate::distributions::Multivariate::sample(&d)
+    }};
+}
+
+#[macro_export]
+macro_rules! von_mises_fisher {
+    ( $var: expr; $mu: expr, $kappa: expr ) => {{
+        let (x, mu) = $crate::functions::promote_slices(&$var[..], &$mu[..]);
+        let (kappa, x) = $crate::functions::promote_slices(&[$kappa], &x);
+        let (_, mu) = $crate::functions::promote_slices(&kappa, &mu);
+        let d = $crate::distributions::VonMisesFisher::new(&mu, kappa[0]);
+        $crate::distributions::Multivariate::unnormalized_log_density(&d, &x[..])
+    }};
+}
+
+#[macro_export]
+macro_rules! von_mises_fisher_lpdf {
+    ( $var: expr; $mu: expr, $kappa: expr ) => {{
+        let (x, mu) = $crate::functions::promote_slices(&$var[..], &$mu[..]);
+        let (kappa, x) = $crate::functions::promote_slices(&[$kappa], &x);
+        let (_, mu) = $crate::functions::promote_slices(&kappa, &mu);
+        let d = $crate::distributions::VonMisesFisher::new(&mu, kappa[0]);
+        $crate::distributions::Multivariate::log_density(&d, &x[..])
+    }};
+}
+
+#[macro_export]
+macro_rules! von_mises_fisher_rng {
+    ( $mu: expr, $kappa: expr ) => {{
+        let (kappa, mu) = $crate::functions::promote_slices(&[$kappa], &$mu[..]);
+        let d = $crate::distributions::VonMisesFisher::new(&mu, kappa[0]);
+        $crate::distributions::Multivariate::sample(&d)
+    }};
+}
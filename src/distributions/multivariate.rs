@@ -0,0 +1,747 @@
+//! Multivariate distributions.
+
+use super::discrete::ln_factorial;
+use super::{is_simplex, linalg, random, Multivariate};
+use crate::functions::{self, Real};
+use std::f64::consts::{LN_2, PI};
+
+/// The factor that a `MultivariateNormal` is parameterized by.
+#[derive(Debug, Clone)]
+enum Factor<T> {
+    /// Cholesky factor of the covariance matrix.
+    Covariance(Vec<Vec<T>>),
+    /// Cholesky factor of the precision matrix.
+    Precision(Vec<Vec<T>>),
+    /// The given matrix was not positive definite.
+    Invalid,
+}
+
+/// The [multivariate normal
+/// distribution](https://en.wikipedia.org/wiki/Multivariate_normal_distribution) with mean `mu`,
+/// parameterized by its covariance matrix, its precision matrix or the Cholesky factor of its
+/// covariance matrix.
+///
+/// Matrices are given as rows. If the covariance or precision matrix is not positive definite,
+/// the log density is -inf.
+#[derive(Debug, Clone)]
+pub struct MultivariateNormal<T> {
+    mu: Vec<T>,
+    factor: Factor<T>,
+}
+
+impl<T: Real> MultivariateNormal<T> {
+    /// Creates a multivariate normal with mean `mu` and covariance matrix `sigma`.
+    pub fn new(mu: &[T], sigma: &[Vec<T>]) -> Self {
+        assert_eq!(mu.len(), sigma.len(), "Dimensions must match.");
+        Self {
+            mu: mu.to_vec(),
+            factor: linalg::cholesky(sigma).map_or(Factor::Invalid, Factor::Covariance),
+        }
+    }
+
+    /// Creates a multivariate normal with mean `mu` and precision (inverse covariance) matrix
+    /// `omega`.
+    pub fn with_precision(mu: &[T], omega: &[Vec<T>]) -> Self {
+        assert_eq!(mu.len(), omega.len(), "Dimensions must match.");
+        Self {
+            mu: mu.to_vec(),
+            factor: linalg::cholesky(omega).map_or(Factor::Invalid, Factor::Precision),
+        }
+    }
+
+    /// Creates a multivariate normal with mean `mu` and covariance matrix `l l^T`, where `l` is
+    /// lower triangular with a positive diagonal. Entries above the diagonal are ignored.
+    pub fn with_cholesky(mu: &[T], l: &[Vec<T>]) -> Self {
+        assert_eq!(mu.len(), l.len(), "Dimensions must match.");
+        let factor = if l.iter().enumerate().all(|(i, row)| row[i] > 0.) {
+            Factor::Covariance(linalg::lower(l))
+        } else {
+            Factor::Invalid
+        };
+        Self {
+            mu: mu.to_vec(),
+            factor,
+        }
+    }
+
+    pub fn dims(&self) -> usize {
+        self.mu.len()
+    }
+}
+
+impl<T: Real> Multivariate<T> for MultivariateNormal<T> {
+    type Support = [T];
+    type Sample = Vec<f64>;
+
+    fn log_density(&self, x: &[T]) -> T {
+        self.unnormalized_log_density(x) - 0.5 * self.dims() as f64 * (2. * PI).ln()
+    }
+
+    fn unnormalized_log_density(&self, x: &[T]) -> T {
+        assert_eq!(x.len(), self.dims(), "Dimensions must match.");
+        let diff = x
+            .iter()
+            .zip(&self.mu)
+            .map(|(&xi, &mi)| xi - mi)
+            .collect::<Vec<_>>();
+        match &self.factor {
+            Factor::Covariance(l) => {
+                -linalg::dot_self(&linalg::forward_solve(l, &diff)) * 0.5 - linalg::log_diagonal(l)
+            }
+            Factor::Precision(l) => {
+                -linalg::dot_self(&linalg::transpose_mul(l, &diff)) * 0.5 + linalg::log_diagonal(l)
+            }
+            Factor::Invalid => x[0].constant(f64::NEG_INFINITY),
+        }
+    }
+
+    fn sample(&self) -> Vec<f64> {
+        let z = (0..self.dims())
+            .map(|_| random::standard_normal())
+            .collect::<Vec<_>>();
+        let offset = match &self.factor {
+            Factor::Covariance(l) => linalg::mul(&linalg::values(l), &z),
+            Factor::Precision(l) => linalg::backward_solve(&linalg::values(l), &z),
+            Factor::Invalid => panic!("Cannot sample with a matrix that is not positive definite."),
+        };
+        self.mu
+            .iter()
+            .zip(offset)
+            .map(|(m, o)| m.value() + o)
+            .collect()
+    }
+}
+
+/// The [multinomial distribution](https://en.wikipedia.org/wiki/Multinomial_distribution) of the
+/// counts in each of K categories with probabilities `theta`, where the number of trials is the
+/// total of the counts.
+///
+/// If the probabilities are negative or do not sum to 1, the log density is -inf.
+#[derive(Debug, Clone)]
+pub struct Multinomial<T> {
+    theta: Vec<T>,
+    valid: bool,
+}
+
+impl<T: Real> Multinomial<T> {
+    pub fn new(theta: &[T]) -> Self {
+        assert!(!theta.is_empty(), "There must be at least one category.");
+        Self {
+            theta: theta.to_vec(),
+            valid: is_simplex(theta),
+        }
+    }
+
+    pub fn dims(&self) -> usize {
+        self.theta.len()
+    }
+
+    /// Draws the counts of `n` trials.
+    pub fn sample_trials(&self, n: i64) -> Vec<i64> {
+        assert!(self.valid, "Cannot sample with invalid probabilities.");
+        // draw each count conditionally on the previous ones
+        let mut remaining = (n, 1.);
+        self.theta
+            .iter()
+            .map(|p| {
+                let (trials, mass) = remaining;
+                let p = (p.value() / mass).min(1.);
+                let k = if mass > 0. {
+                    random::binomial(trials, p)
+                } else {
+                    0
+                };
+                remaining = (trials - k, mass - p * mass);
+                k
+            })
+            .collect()
+    }
+}
+
+impl<T: Real> Multivariate<T> for Multinomial<T> {
+    type Support = [i64];
+    type Sample = Vec<i64>;
+
+    fn log_density(&self, n: &[i64]) -> T {
+        if !self.valid || n.iter().any(|&k| k < 0) {
+            return self.theta[0].constant(f64::NEG_INFINITY);
+        }
+        let log_coefficient =
+            ln_factorial(n.iter().sum()) - n.iter().map(|&k| ln_factorial(k)).sum::<f64>();
+        self.unnormalized_log_density(n) + log_coefficient
+    }
+
+    fn unnormalized_log_density(&self, n: &[i64]) -> T {
+        assert_eq!(n.len(), self.dims(), "Dimensions must match.");
+        if !self.valid || n.iter().any(|&k| k < 0) {
+            return self.theta[0].constant(f64::NEG_INFINITY);
+        }
+        // skip the categories with no counts, which would be 0 * -inf when their probability is 0
+        n.iter()
+            .zip(&self.theta)
+            .filter(|(&k, _)| k > 0)
+            .fold(self.theta[0].constant(0.), |acc, (&k, &p)| {
+                acc + p.ln() * k as f64
+            })
+    }
+
+    /// Draws the counts of a single trial.
+    fn sample(&self) -> Vec<i64> {
+        self.sample_trials(1)
+    }
+}
+
+/// The [Dirichlet distribution](https://en.wikipedia.org/wiki/Dirichlet_distribution) over
+/// probability vectors, with concentrations `alpha`.
+#[derive(Debug, Clone)]
+pub struct Dirichlet<T> {
+    pub alpha: Vec<T>,
+}
+
+impl<T: Real> Dirichlet<T> {
+    pub fn new(alpha: &[T]) -> Self {
+        assert!(alpha.len() > 1, "There must be at least two categories.");
+        Self {
+            alpha: alpha.to_vec(),
+        }
+    }
+
+    pub fn dims(&self) -> usize {
+        self.alpha.len()
+    }
+}
+
+impl<T: Real + functions::Gamma> Multivariate<T> for Dirichlet<T> {
+    type Support = [T];
+    type Sample = Vec<f64>;
+
+    fn log_density(&self, x: &[T]) -> T {
+        assert_eq!(x.len(), self.dims(), "Dimensions must match.");
+        if self.alpha.iter().any(|&a| a <= 0.) || x.iter().any(|&p| p <= 0.) || !is_simplex(x) {
+            return x[0].constant(f64::NEG_INFINITY);
+        }
+        let sum = self.alpha[1..]
+            .iter()
+            .fold(self.alpha[0], |acc, &a| acc + a);
+        x.iter()
+            .zip(&self.alpha)
+            .fold(functions::lgamma(sum), |acc, (&p, &a)| {
+                acc - functions::lgamma(a) + (a - 1.) * p.ln()
+            })
+    }
+
+    fn sample(&self) -> Vec<f64> {
+        let draws = self
+            .alpha
+            .iter()
+            .map(|a| random::standard_gamma(a.value()))
+            .collect::<Vec<_>>();
+        let total: f64 = draws.iter().sum();
+        draws.into_iter().map(|g| g / total).collect()
+    }
+}
+
+/// Calculates the log of the normalizing constant of the LKJ distribution, from Lewandowski,
+/// Kurowicka and Joe (2009).
+fn lkj_log_constant<T: Real + functions::Gamma>(eta: T, dims: usize) -> T {
+    let k = dims as f64;
+    (1..dims).fold(eta.constant(0.), |acc, i| {
+        let i = i as f64;
+        let b = eta + (k - i - 1.) / 2.;
+        let ln_beta = functions::lbeta(b, b);
+        acc + ((eta * 2. - 2. + k - i) * LN_2 + ln_beta) * (k - i)
+    })
+}
+
+/// Draws the Cholesky factor of an LKJ correlation matrix with the C-vine method, from partial
+/// correlations that are beta distributed.
+fn lkj_cholesky_sample(eta: f64, dims: usize) -> Vec<Vec<f64>> {
+    let mut l = (0..dims).map(|i| vec![0.; i + 1]).collect::<Vec<_>>();
+    let mut remaining = vec![1.; dims];
+    for i in 0..dims {
+        l[i][i] = remaining[i].sqrt();
+        let alpha = eta + (dims as f64 - 2. - i as f64) / 2.;
+        for j in i + 1..dims {
+            let partial = 2. * random::beta(alpha, alpha) - 1.;
+            l[j][i] = partial * remaining[j].sqrt();
+            remaining[j] *= 1. - partial * partial;
+        }
+    }
+    l
+}
+
+/// The [LKJ distribution](https://en.wikipedia.org/wiki/Lewandowski-Kurowicka-Joe_distribution)
+/// over `dims` by `dims` correlation matrices, with shape `eta`. Values of `eta` greater than 1
+/// favour weaker correlations.
+///
+/// If the variable is not a correlation matrix, the log density is -inf.
+#[derive(Debug, Clone, Copy)]
+pub struct LkjCorr<T> {
+    pub eta: T,
+    pub dims: usize,
+}
+
+impl<T: Real> LkjCorr<T> {
+    pub fn new(eta: T, dims: usize) -> Self {
+        Self { eta, dims }
+    }
+}
+
+impl<T: Real + functions::Gamma> Multivariate<T> for LkjCorr<T> {
+    type Support = [Vec<T>];
+    type Sample = Vec<Vec<f64>>;
+
+    fn log_density(&self, omega: &[Vec<T>]) -> T {
+        assert_eq!(omega.len(), self.dims, "Dimensions must match.");
+        let unit_diagonal = omega
+            .iter()
+            .enumerate()
+            .all(|(i, row)| (row[i].value() - 1.).abs() < 1e-8);
+        match linalg::cholesky(omega) {
+            Some(l) if unit_diagonal && self.eta > 0. => {
+                (self.eta - 1.) * linalg::log_diagonal(&l) * 2.
+                    - lkj_log_constant(self.eta, self.dims)
+            }
+            _ => self.eta.constant(f64::NEG_INFINITY),
+        }
+    }
+
+    fn sample(&self) -> Vec<Vec<f64>> {
+        linalg::gram(&lkj_cholesky_sample(self.eta.value(), self.dims))
+    }
+}
+
+/// The distribution of the Cholesky factor of a `dims` by `dims` correlation matrix with an LKJ
+/// distribution with shape `eta`. This is cheaper than `LkjCorr` when the model works with the
+/// Cholesky factor anyway, for example with `MultivariateNormal::with_cholesky`.
+///
+/// The variable is lower triangular, and entries above the diagonal are ignored. If it is not the
+/// Cholesky factor of a correlation matrix, the log density is -inf.
+#[derive(Debug, Clone, Copy)]
+pub struct LkjCorrCholesky<T> {
+    pub eta: T,
+    pub dims: usize,
+}
+
+impl<T: Real> LkjCorrCholesky<T> {
+    pub fn new(eta: T, dims: usize) -> Self {
+        Self { eta, dims }
+    }
+}
+
+impl<T: Real + functions::Gamma> Multivariate<T> for LkjCorrCholesky<T> {
+    type Support = [Vec<T>];
+    type Sample = Vec<Vec<f64>>;
+
+    fn log_density(&self, l: &[Vec<T>]) -> T {
+        assert_eq!(l.len(), self.dims, "Dimensions must match.");
+        let l = linalg::lower(l);
+        let valid = l
+            .iter()
+            .enumerate()
+            .all(|(i, row)| row[i] > 0. && (linalg::dot_self(row).value() - 1.).abs() < 1e-8);
+        if !valid || self.eta <= 0. {
+            return self.eta.constant(f64::NEG_INFINITY);
+        }
+        let k = self.dims as f64;
+        // includes the Jacobian of the map from the Cholesky factor to the correlation matrix
+        (1..self.dims).fold(-lkj_log_constant(self.eta, self.dims), |acc, i| {
+            acc + (self.eta * 2. - 2. + k - i as f64 - 1.) * l[i][i].ln()
+        })
+    }
+
+    fn sample(&self) -> Vec<Vec<f64>> {
+        linalg::square(&lkj_cholesky_sample(self.eta.value(), self.dims))
+    }
+}
+
+/// Calculates the log of the multivariate gamma function Γ_p(a), without the constant
+/// p (p - 1) / 4 ln(pi).
+fn ln_multivariate_gamma<T: Real + functions::Gamma>(a: T, p: usize) -> T {
+    (0..p).fold(a.constant(0.), |acc, j| {
+        acc + functions::lgamma(a - j as f64 / 2.)
+    })
+}
+
+/// Draws the lower triangular factor `A` of the Bartlett decomposition, so that `A A^T` has a
+/// Wishart distribution with `nu` degrees of freedom and an identity scale matrix.
+fn bartlett_sample(nu: f64, dims: usize) -> Vec<Vec<f64>> {
+    (0..dims)
+        .map(|i| {
+            let mut row = (0..i)
+                .map(|_| random::standard_normal())
+                .collect::<Vec<_>>();
+            row.push((2. * random::standard_gamma((nu - i as f64) / 2.)).sqrt());
+            row
+        })
+        .collect()
+}
+
+/// The [Wishart distribution](https://en.wikipedia.org/wiki/Wishart_distribution) over symmetric
+/// positive definite matrices, with `nu` degrees of freedom and scale matrix `sigma`.
+///
+/// Matrices are given as rows. If the scale matrix or the variable is not positive definite, or
+/// `nu` is not greater than the dimension minus 1, the log density is -inf.
+#[derive(Debug, Clone)]
+pub struct Wishart<T> {
+    nu: T,
+    factor: Option<Vec<Vec<T>>>,
+}
+
+impl<T: Real> Wishart<T> {
+    /// Creates a Wishart distribution with `nu` degrees of freedom and scale matrix `sigma`.
+    pub fn new(nu: T, sigma: &[Vec<T>]) -> Self {
+        Self {
+            nu,
+            factor: linalg::cholesky(sigma),
+        }
+    }
+
+    /// Creates a Wishart distribution with `nu` degrees of freedom and scale matrix `l l^T`, where
+    /// `l` is lower triangular with a positive diagonal. Entries above the diagonal are ignored.
+    pub fn with_cholesky(nu: T, l: &[Vec<T>]) -> Self {
+        let valid = l.iter().enumerate().all(|(i, row)| row[i] > 0.);
+        Self {
+            nu,
+            factor: if valid { Some(linalg::lower(l)) } else { None },
+        }
+    }
+}
+
+impl<T: Real + functions::Gamma> Multivariate<T> for Wishart<T> {
+    type Support = [Vec<T>];
+    type Sample = Vec<Vec<f64>>;
+
+    fn log_density(&self, w: &[Vec<T>]) -> T {
+        let p = w.len() as f64;
+        self.unnormalized_log_density(w) - p * (p - 1.) / 4. * PI.ln()
+    }
+
+    fn unnormalized_log_density(&self, w: &[Vec<T>]) -> T {
+        let p = w.len();
+        let (l, m) = match (&self.factor, linalg::cholesky(w)) {
+            (Some(l), Some(m)) if self.nu > p as f64 - 1. => (l, m),
+            _ => return self.nu.constant(f64::NEG_INFINITY),
+        };
+        assert_eq!(l.len(), p, "Dimensions must match.");
+        let p = p as f64;
+        (self.nu - p - 1.) * linalg::log_diagonal(&m)
+            - linalg::trace_solve(l, &m) * 0.5
+            - self.nu * p * 0.5 * LN_2
+            - self.nu * linalg::log_diagonal(l)
+            - ln_multivariate_gamma(self.nu * 0.5, l.len())
+    }
+
+    fn sample(&self) -> Vec<Vec<f64>> {
+        let l = linalg::values(
+            self.factor
+                .as_ref()
+                .expect("Cannot sample with a scale matrix that is not positive definite."),
+        );
+        let a = bartlett_sample(self.nu.value(), l.len());
+        linalg::gram(&linalg::lower_mul(&l, &a))
+    }
+}
+
+/// The [inverse Wishart distribution](https://en.wikipedia.org/wiki/Inverse-Wishart_distribution)
+/// over symmetric positive definite matrices, with `nu` degrees of freedom and scale matrix `psi`.
+///
+/// Matrices are given as rows. If the scale matrix or the variable is not positive definite, or
+/// `nu` is not greater than the dimension minus 1, the log density is -inf.
+#[derive(Debug, Clone)]
+pub struct InverseWishart<T> {
+    nu: T,
+    factor: Option<Vec<Vec<T>>>,
+}
+
+impl<T: Real> InverseWishart<T> {
+    /// Creates an inverse Wishart distribution with `nu` degrees of freedom and scale matrix
+    /// `psi`.
+    pub fn new(nu: T, psi: &[Vec<T>]) -> Self {
+        Self {
+            nu,
+            factor: linalg::cholesky(psi),
+        }
+    }
+
+    /// Creates an inverse Wishart distribution with `nu` degrees of freedom and scale matrix
+    /// `l l^T`, where `l` is lower triangular with a positive diagonal. Entries above the diagonal
+    /// are ignored.
+    pub fn with_cholesky(nu: T, l: &[Vec<T>]) -> Self {
+        let valid = l.iter().enumerate().all(|(i, row)| row[i] > 0.);
+        Self {
+            nu,
+            factor: if valid { Some(linalg::lower(l)) } else { None },
+        }
+    }
+}
+
+impl<T: Real + functions::Gamma> Multivariate<T> for InverseWishart<T> {
+    type Support = [Vec<T>];
+    type Sample = Vec<Vec<f64>>;
+
+    fn log_density(&self, x: &[Vec<T>]) -> T {
+        let p = x.len() as f64;
+        self.unnormalized_log_density(x) - p * (p - 1.) / 4. * PI.ln()
+    }
+
+    fn unnormalized_log_density(&self, x: &[Vec<T>]) -> T {
+        let p = x.len();
+        let (l, m) = match (&self.factor, linalg::cholesky(x)) {
+            (Some(l), Some(m)) if self.nu > p as f64 - 1. => (l, m),
+            _ => return self.nu.constant(f64::NEG_INFINITY),
+        };
+        assert_eq!(l.len(), p, "Dimensions must match.");
+        let p = p as f64;
+        self.nu * linalg::log_diagonal(l)
+            - (self.nu + p + 1.) * linalg::log_diagonal(&m)
+            - linalg::trace_solve(&m, l) * 0.5
+            - self.nu * p * 0.5 * LN_2
+            - ln_multivariate_gamma(self.nu * 0.5, l.len())
+    }
+
+    fn sample(&self) -> Vec<Vec<f64>> {
+        let l = linalg::values(
+            self.factor
+                .as_ref()
+                .expect("Cannot sample with a scale matrix that is not positive definite."),
+        );
+        // L (A A^T)^-1 L^T has an inverse Wishart distribution, where A A^T is Wishart with an
+        // identity scale matrix. The rows of L A^-T solve A g = l for the rows l of L.
+        let a = bartlett_sample(self.nu.value(), l.len());
+        let g = linalg::square(&l)
+            .iter()
+            .map(|row| linalg::forward_solve(&a, row))
+            .collect::<Vec<_>>();
+        linalg::gram(&g)
+    }
+}
+
+/// Checks whether `x` has unit length.
+fn is_unit<T: Real>(x: &[T]) -> bool {
+    let norm: f64 = x.iter().map(|v| v.value().powi(2)).sum();
+    (norm - 1.).abs() < 1e-8
+}
+
+/// The [von Mises–Fisher
+/// distribution](https://en.wikipedia.org/wiki/Von_Mises%E2%80%93Fisher_distribution) of
+/// directions on the unit sphere, with mean direction `mu` and concentration `kappa`.
+///
+/// If `mu` is not a unit vector, the log density is -inf.
+#[derive(Debug, Clone)]
+pub struct VonMisesFisher<T> {
+    pub mu: Vec<T>,
+    pub kappa: T,
+}
+
+impl<T: Real> VonMisesFisher<T> {
+    pub fn new(mu: &[T], kappa: T) -> Self {
+        assert!(mu.len() >= 2, "There must be at least two dimensions.");
+        Self {
+            mu: mu.to_vec(),
+            kappa,
+        }
+    }
+
+    pub fn dims(&self) -> usize {
+        self.mu.len()
+    }
+}
+
+impl<T: Real + functions::Bessel> Multivariate<T> for VonMisesFisher<T> {
+    type Support = [T];
+    type Sample = Vec<f64>;
+
+    fn log_density(&self, x: &[T]) -> T {
+        self.unnormalized_log_density(x) - 0.5 * self.dims() as f64 * (2. * PI).ln()
+    }
+
+    fn unnormalized_log_density(&self, x: &[T]) -> T {
+        assert_eq!(x.len(), self.dims(), "Dimensions must match.");
+        if self.kappa < 0. || !is_unit(&self.mu) || !is_unit(x) {
+            return self.kappa.constant(f64::NEG_INFINITY);
+        }
+        let nu = 0.5 * self.dims() as f64 - 1.;
+        // kappa^nu / I_nu(kappa) tends to 2^nu Γ(nu + 1) for a uniform distribution
+        let log_constant = if self.kappa.value() == 0. {
+            self.kappa.constant(nu * LN_2 + functions::lgamma(nu + 1.))
+        } else {
+            self.kappa.ln() * nu - functions::log_bessel_i(nu, self.kappa)
+        };
+        let dot = x
+            .iter()
+            .zip(&self.mu)
+            .fold(self.kappa.constant(0.), |acc, (&xi, &mi)| acc + xi * mi);
+        log_constant + self.kappa * dot
+    }
+
+    /// Draws a random sample with the algorithm of Wood (1994).
+    fn sample(&self) -> Vec<f64> {
+        let mu = self.mu.iter().map(|m| m.value()).collect::<Vec<_>>();
+        assert!(
+            is_unit(&mu),
+            "Cannot sample with a mean direction that is not a unit vector."
+        );
+        let (kappa, p) = (self.kappa.value(), self.dims() as f64);
+
+        // the component along mu
+        let w = if kappa < 1e-8 {
+            2. * random::beta(0.5 * (p - 1.), 0.5 * (p - 1.)) - 1.
+        } else {
+            let b = (p - 1.) / (2. * kappa + (4. * kappa * kappa + (p - 1.).powi(2)).sqrt());
+            let x0 = (1. - b) / (1. + b);
+            let c = kappa * x0 + (p - 1.) * (1. - x0 * x0).ln();
+            loop {
+                let z = random::beta(0.5 * (p - 1.), 0.5 * (p - 1.));
+                let w = (1. - (1. + b) * z) / (1. - (1. - b) * z);
+                if kappa * w + (p - 1.) * (1. - x0 * w).ln() - c >= random::uniform().ln() {
+                    break w;
+                }
+            }
+        };
+
+        // a uniformly random direction orthogonal to mu
+        let n = mu
+            .iter()
+            .map(|_| random::standard_normal())
+            .collect::<Vec<_>>();
+        let along = n.iter().zip(&mu).map(|(a, b)| a * b).sum::<f64>();
+        let v = n
+            .iter()
+            .zip(&mu)
+            .map(|(a, b)| a - along * b)
+            .collect::<Vec<_>>();
+        let norm = v.iter().map(|a| a * a).sum::<f64>().sqrt();
+        let scale = (1. - w * w).max(0.).sqrt() / norm;
+        mu.iter().zip(&v).map(|(m, a)| w * m + scale * a).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::distributions::assert_close;
+
+    #[test]
+    fn multivariate_normal_factors() {
+        let mu = [1., -0.5];
+        let sigma = vec![vec![2., 0.6], vec![0.6, 1.]];
+        let det = 2. - 0.6 * 0.6;
+        let omega = vec![vec![1. / det, -0.6 / det], vec![-0.6 / det, 2. / det]];
+        let l = vec![
+            vec![2_f64.sqrt(), 0.],
+            vec![0.6 / 2_f64.sqrt(), (1. - 0.18_f64).sqrt()],
+        ];
+        let x = [0.3, 0.4];
+        let expected = -2.959005675132277;
+        assert_close(
+            MultivariateNormal::new(&mu, &sigma).log_density(&x),
+            expected,
+        );
+        assert_close(
+            MultivariateNormal::with_precision(&mu, &omega).log_density(&x),
+            expected,
+        );
+        assert_close(
+            MultivariateNormal::with_cholesky(&mu, &l).log_density(&x),
+            expected,
+        );
+
+        let singular = vec![vec![1., 1.], vec![1., 1.]];
+        let d = MultivariateNormal::with_precision(&mu, &singular);
+        assert_eq!(d.log_density(&x), f64::NEG_INFINITY);
+    }
+
+    #[test]
+    fn dirichlet_and_multinomial() {
+        let d = Dirichlet::new(&[2., 3., 1.5]);
+        assert_close(d.log_density(&[0.2, 0.5, 0.3]), 1.4924784412154826);
+        assert_eq!(d.log_density(&[0.2, 0.5, 0.4]), f64::NEG_INFINITY);
+
+        let d = Multinomial::new(&[0.2, 0.5, 0.3]);
+        assert_close(d.log_density(&[1, 3, 2]), -2.0024805005437076);
+        // a category with probability 0 and no counts leaves the mass unchanged
+        let d = Multinomial::new(&[0., 0.4, 0.6]);
+        assert_close(d.log_density(&[0, 2, 1]), -1.2447947988461912);
+        assert_eq!(d.log_density(&[1, 2, 1]), f64::NEG_INFINITY);
+    }
+
+    #[test]
+    fn lkj_constant() {
+        let eta = 2.5;
+        let expected = (2. * eta - 1.) * LN_2 + functions::lbeta(eta, eta);
+        assert_close(lkj_log_constant(eta, 2), expected);
+        // the volume of the 3 by 3 correlation matrices is pi^2 / 2
+        assert_close(lkj_log_constant(1., 3), 1.596312591138855);
+
+        let r = 0.3;
+        let omega = vec![vec![1., r], vec![r, 1.]];
+        assert_close(
+            LkjCorr::new(eta, 2).log_density(&omega),
+            -0.3053666520445359,
+        );
+    }
+
+    #[test]
+    fn lkj_cholesky_jacobian() {
+        let eta = 1.7;
+        let l = vec![
+            vec![1., 0., 0.],
+            vec![0.6, 0.8, 0.],
+            vec![-0.3, 0.4, (1. - 0.09_f64 - 0.16).sqrt()],
+        ];
+        let omega = linalg::gram(&l);
+        // the map from the entries below the diagonal of l to those of omega has Jacobian l[1][1]
+        assert_close(
+            LkjCorrCholesky::new(eta, 3).log_density(&l),
+            LkjCorr::new(eta, 3).log_density(&omega) + 0.8_f64.ln(),
+        );
+        // in two dimensions, l[1][1] is determined by omega[1][0], so there is no Jacobian
+        let l = vec![vec![1., 0.], vec![0.6, 0.8]];
+        assert_close(
+            LkjCorrCholesky::new(eta, 2).log_density(&l),
+            LkjCorr::new(eta, 2).log_density(&linalg::gram(&l)),
+        );
+    }
+
+    #[test]
+    fn wishart_trace_order() {
+        let sigma = vec![vec![2., 0.5], vec![0.5, 1.]];
+        let w = vec![vec![1.5, -0.3], vec![-0.3, 0.8]];
+        assert_close(
+            Wishart::new(4.5, &sigma).log_density(&w),
+            -5.884291932106455,
+        );
+        assert_close(
+            InverseWishart::new(4.5, &sigma).log_density(&w),
+            -4.395743915459106,
+        );
+
+        let l = linalg::cholesky(&sigma).unwrap();
+        assert_close(
+            Wishart::with_cholesky(4.5, &l).log_density(&w),
+            -5.884291932106455,
+        );
+        assert_eq!(Wishart::new(0.5, &sigma).log_density(&w), f64::NEG_INFINITY);
+    }
+
+    #[test]
+    fn von_mises_fisher_constant() {
+        let (mu, x) = ([0., 0.6, 0.8], [0.48, 0.6, -0.64]);
+        assert_close(
+            VonMisesFisher::new(&mu, 2.).log_density(&x),
+            -3.4302444390235136,
+        );
+        // kappa = 0 is uniform on the sphere
+        assert_close(
+            VonMisesFisher::new(&mu, 0.).log_density(&x),
+            -2.5310242469692907,
+        );
+        assert_close(
+            VonMisesFisher::new(&mu, 1e-10).log_density(&x),
+            -2.5310242469692907 - 1e-10 * 0.152,
+        );
+        let d = VonMisesFisher::new(&[1., 0.], 0.);
+        assert_close(d.log_density(&[0., 1.]), -1.8378770664093456);
+    }
+}
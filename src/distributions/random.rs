@@ -0,0 +1,128 @@
+//! Random number generation for the distributions.
+
+use crate::functions;
+
+/// Seeds the random number generator, so that draws from the distributions are reproducible. The
+/// generator is local to each thread, so this only affects the thread it is called from. The
+/// samplers draw their proposals from a different generator, so their output is not reproducible.
+pub fn set_seed(seed: u64) {
+    alea::set_seed(seed);
+}
+
+/// Draws from the uniform distribution on (0, 1).
+pub(crate) fn uniform() -> f64 {
+    loop {
+        let u = alea::f64();
+        if u > 0. {
+            return u;
+        }
+    }
+}
+
+/// Draws from the standard normal distribution using the Marsaglia polar method.
+pub(crate) fn standard_normal() -> f64 {
+    loop {
+        let u = 2. * alea::f64() - 1.;
+        let v = 2. * alea::f64() - 1.;
+        let s = u * u + v * v;
+        if s > 0. && s < 1. {
+            return u * (-2. * s.ln() / s).sqrt();
+        }
+    }
+}
+
+/// Draws from the standard exponential distribution.
+pub(crate) fn standard_exponential() -> f64 {
+    -uniform().ln()
+}
+
+/// Draws from the gamma distribution with unit rate using the method of Marsaglia and Tsang
+/// (2000).
+pub(crate) fn standard_gamma(shape: f64) -> f64 {
+    if shape < 1. {
+        return standard_gamma(shape + 1.) * uniform().powf(1. / shape);
+    }
+    let d = shape - 1. / 3.;
+    let c = 1. / (9. * d).sqrt();
+    loop {
+        let (x, v) = loop {
+            let x = standard_normal();
+            let v = 1. + c * x;
+            if v > 0. {
+                break (x, v * v * v);
+            }
+        };
+        let u = uniform();
+        if u < 1. - 0.0331 * x.powi(4) || u.ln() < 0.5 * x * x + d * (1. - v + v.ln()) {
+            return d * v;
+        }
+    }
+}
+
+/// Draws from the beta distribution.
+pub(crate) fn beta(alpha: f64, beta: f64) -> f64 {
+    let x = standard_gamma(alpha);
+    let y = standard_gamma(beta);
+    x / (x + y)
+}
+
+/// Draws from the Poisson distribution, by multiplying uniforms for small rates and with the
+/// PTRS algorithm of Hörmann (1993) otherwise.
+pub(crate) fn poisson(lambda: f64) -> i64 {
+    if lambda < 10. {
+        let limit = (-lambda).exp();
+        let mut k = 0;
+        let mut p = uniform();
+        while p > limit {
+            k += 1;
+            p *= uniform();
+        }
+        return k;
+    }
+
+    let slam = lambda.sqrt();
+    let loglam = lambda.ln();
+    let b = 0.931 + 2.53 * slam;
+    let a = -0.059 + 0.02483 * b;
+    let invalpha = 1.1239 + 1.1328 / (b - 3.4);
+    let vr = 0.9277 - 3.6224 / (b - 2.);
+
+    loop {
+        let u = alea::f64() - 0.5;
+        let v = uniform();
+        let us = 0.5 - u.abs();
+        let k = ((2. * a / us + b) * u + lambda + 0.43).floor();
+        if us >= 0.07 && v <= vr {
+            return k as i64;
+        }
+        if k < 0. || (us < 0.013 && v > us) {
+            continue;
+        }
+        if v.ln() + invalpha.ln() - (a / (us * us) + b).ln()
+            <= -lambda + k * loglam - functions::lfactorial(k)
+        {
+            return k as i64;
+        }
+    }
+}
+
+/// Draws from the binomial distribution. Large numbers of trials are split up using beta draws
+/// (Knuth, TAOCP vol. 2, 3.4.1), and the rest are counted one by one.
+pub(crate) fn binomial(n: i64, p: f64) -> i64 {
+    let (mut n, mut p) = (n, p);
+    let mut k = 0;
+    while n > 64 {
+        let a = 1 + n / 2;
+        let b = n + 1 - a;
+        let x = beta(a as f64, b as f64);
+        if x >= p {
+            n = a - 1;
+            p /= x;
+        } else {
+            k += a;
+            n = b - 1;
+            p = (p - x) / (1. - x);
+        }
+    }
+    k + (0..n).filter(|_| alea::f64() < p).count() as i64
+}
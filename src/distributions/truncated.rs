@@ -0,0 +1,156 @@
+//! Truncated distributions.
+
+use super::{Cdf, Distribution, Variate};
+use crate::functions::{self, Real};
+
+/// A univariate distribution `dist` restricted to the interval [`lower`, `upper`], where either
+/// bound may be missing. The log density is that of `dist` minus the log of its mass over the
+/// interval, and -inf outside of it.
+///
+/// For discrete distributions both bounds are included, so the mass is `P(lower <= X <= upper)`.
+#[derive(Debug, Clone, Copy)]
+pub struct Truncated<D, S> {
+    pub dist: D,
+    pub lower: Option<S>,
+    pub upper: Option<S>,
+}
+
+impl<D, S: Variate> Truncated<D, S> {
+    pub fn new(dist: D, lower: Option<S>, upper: Option<S>) -> Self {
+        Self { dist, lower, upper }
+    }
+
+    /// Checks whether `x` is within the bounds.
+    fn contains(&self, x: f64) -> bool {
+        self.lower.is_none_or(|l| x >= l.to_f64()) && self.upper.is_none_or(|u| x <= u.to_f64())
+    }
+}
+
+/// Calculates the log of the mass of `dist` within [`lower`, `upper`], or `None` if there are no
+/// bounds.
+pub(super) fn log_mass<T: Real, D: Cdf<T>>(
+    dist: &D,
+    lower: Option<D::Support>,
+    upper: Option<D::Support>,
+) -> Option<T>
+where
+    D::Support: Variate,
+{
+    match (lower, upper) {
+        (None, None) => None,
+        (Some(l), None) => Some(dist.lccdf(l.predecessor())),
+        (None, Some(u)) => Some(dist.lcdf(u)),
+        (Some(l), Some(u)) => {
+            let upper = dist.lcdf(u);
+            let lower = dist.lcdf(l.predecessor());
+            Some(functions::log_diff_exp(upper, lower))
+        }
+    }
+}
+
+impl<T: Real, D: Cdf<T>> Distribution<T> for Truncated<D, D::Support>
+where
+    D::Support: Variate,
+{
+    type Support = D::Support;
+
+    fn log_density(&self, x: D::Support) -> T {
+        let log_density = self.dist.log_density(x);
+        if !self.contains(x.to_f64()) {
+            return log_density.constant(f64::NEG_INFINITY);
+        }
+        match log_mass(&self.dist, self.lower, self.upper) {
+            Some(mass) => log_density - mass,
+            None => log_density,
+        }
+    }
+
+    fn unnormalized_log_density(&self, x: D::Support) -> T {
+        let log_density = self.dist.unnormalized_log_density(x);
+        if !self.contains(x.to_f64()) {
+            return log_density.constant(f64::NEG_INFINITY);
+        }
+        // the mass depends on the parameters, so it is always kept
+        match log_mass(&self.dist, self.lower, self.upper) {
+            Some(mass) => log_density - mass,
+            None => log_density,
+        }
+    }
+
+    /// Draws a random sample by rejection, which is slow if the interval has little mass.
+    fn sample(&self) -> f64 {
+        loop {
+            let x = self.dist.sample();
+            if self.contains(x) {
+                return x;
+            }
+        }
+    }
+}
+
+impl<T: Real, D: Cdf<T>> Cdf<T> for Truncated<D, D::Support>
+where
+    D::Support: Variate,
+{
+    fn cdf(&self, x: D::Support) -> T {
+        let cdf = self.dist.cdf(x);
+        if self.lower.is_some_and(|l| x.to_f64() < l.to_f64()) {
+            return cdf.constant(0.);
+        }
+        if self.upper.is_some_and(|u| x.to_f64() >= u.to_f64()) {
+            return cdf.constant(1.);
+        }
+        let below = match self.lower {
+            Some(l) => self.dist.cdf(l.predecessor()),
+            None => cdf.constant(0.),
+        };
+        match log_mass(&self.dist, self.lower, self.upper) {
+            Some(mass) => (cdf - below) / mass.exp(),
+            None => cdf,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::distributions::{assert_close, Exponential, Normal, Pareto, Poisson, Rayleigh};
+
+    #[test]
+    fn normal_mass() {
+        let d = Truncated::new(Normal::new(0., 1.), Some(-1.), Some(2.));
+        assert_close(d.log_density(0.5), -0.84377223888021016183);
+        assert_close(d.cdf(0.5), 0.65088042133662712997);
+        assert_eq!(d.log_density(2.5), f64::NEG_INFINITY);
+        assert_eq!(d.cdf(-2.), 0.);
+        assert_eq!(d.cdf(3.), 1.);
+
+        let d = Truncated::new(Normal::new(1., 2.), Some(0.), None);
+        assert_close(d.log_density(1.5), -1.2743892984759616581);
+    }
+
+    #[test]
+    fn discrete_bounds_are_inclusive() {
+        let d = Truncated::new(Poisson::new(2.5), Some(1), None);
+        assert_close(d.log_density(3), -1.4572367898635516241);
+        assert_eq!(d.log_density(0), f64::NEG_INFINITY);
+    }
+
+    #[test]
+    fn bounds_below_the_support() {
+        let exponential = Exponential::new(1.5);
+        let d = Truncated::new(exponential, Some(-1.), None);
+        assert_close(d.log_density(0.5), exponential.log_density(0.5));
+
+        let rayleigh = Rayleigh::new(1.2);
+        let d = Truncated::new(rayleigh, Some(-2.), Some(1.));
+        let d0 = Truncated::new(rayleigh, Some(0.), Some(1.));
+        assert_close(d.log_density(0.7), d0.log_density(0.7));
+
+        let pareto = Pareto::new(0.5, 3.);
+        assert_eq!(pareto.cdf(0.2), 0.);
+        assert_eq!(pareto.lccdf(0.2), 0.);
+        let d = Truncated::new(pareto, Some(0.1), None);
+        assert_close(d.log_density(0.7), pareto.log_density(0.7));
+    }
+}
@@ -0,0 +1,523 @@
+//! Vectorized log densities, summed over many observations at once.
+//!
+//! Each argument is either a scalar, which is broadcast to every observation, or a slice, `Vec` or
+//! `Vector` with one value per observation. The sum and its partial derivatives are computed in
+//! `f64` in a single pass, and if any argument holds `Var`s, the result is recorded as one tape
+//! node per `Var` argument (see `Fuse`) rather than a handful of nodes per observation. The macros
+//! of these distributions (e.g. `normal!(y; mu, sigma)`) are built on them.
+
+use super::discrete::ln_factorial;
+use crate::functions::{self, Fuse, Promote};
+use compute::linalg::Vector;
+use reverse::Var;
+use std::f64::consts::{LN_2, PI};
+
+/// An argument of a vectorized log density. `Elem` is `Var` if it holds `Var`s and `f64`
+/// otherwise, which decides whether the log density is a `Var`.
+pub trait Broadcast<'a> {
+    type Elem;
+    fn column(&self) -> Column<'_, 'a>;
+}
+
+/// The values of a `Broadcast` argument.
+pub struct Column<'s, 'a> {
+    values: Values<'s, 'a>,
+    scalar: bool,
+}
+
+enum Values<'s, 'a> {
+    F64(&'s [f64]),
+    I64(&'s [i64]),
+    I32(&'s [i32]),
+    Var(&'s [Var<'a>]),
+}
+
+impl<'s, 'a> Column<'s, 'a> {
+    fn len(&self) -> usize {
+        match self.values {
+            Values::F64(v) => v.len(),
+            Values::I64(v) => v.len(),
+            Values::I32(v) => v.len(),
+            Values::Var(v) => v.len(),
+        }
+    }
+
+    /// The index of the value for observation `i`.
+    fn index(&self, i: usize) -> usize {
+        if self.scalar {
+            0
+        } else {
+            i
+        }
+    }
+
+    fn value(&self, i: usize) -> f64 {
+        let i = self.index(i);
+        match self.values {
+            Values::F64(v) => v[i],
+            Values::I64(v) => v[i] as f64,
+            Values::I32(v) => v[i] as f64,
+            Values::Var(v) => v[i].val(),
+        }
+    }
+}
+
+macro_rules! broadcast {
+    ( $t: ty, $variant: ident ) => {
+        impl<'a> Broadcast<'a> for $t {
+            type Elem = f64;
+            fn column(&self) -> Column<'_, 'a> {
+                Column {
+                    values: Values::$variant(std::slice::from_ref(self)),
+                    scalar: true,
+                }
+            }
+        }
+
+        impl<'a> Broadcast<'a> for [$t] {
+            type Elem = f64;
+            fn column(&self) -> Column<'_, 'a> {
+                Column {
+                    values: Values::$variant(self),
+                    scalar: false,
+                }
+            }
+        }
+
+        impl<'a, const N: usize> Broadcast<'a> for [$t; N] {
+            type Elem = f64;
+            fn column(&self) -> Column<'_, 'a> {
+                self[..].column()
+            }
+        }
+
+        impl<'a> Broadcast<'a> for Vec<$t> {
+            type Elem = f64;
+            fn column(&self) -> Column<'_, 'a> {
+                self[..].column()
+            }
+        }
+    };
+}
+
+broadcast!(f64, F64);
+broadcast!(i64, I64);
+broadcast!(i32, I32);
+
+impl<'a> Broadcast<'a> for Vector {
+    type Elem = f64;
+    fn column(&self) -> Column<'_, 'a> {
+        let values: &[f64] = self;
+        values.column()
+    }
+}
+
+impl<'a> Broadcast<'a> for Var<'a> {
+    type Elem = Var<'a>;
+    fn column(&self) -> Column<'_, 'a> {
+        Column {
+            values: Values::Var(std::slice::from_ref(self)),
+            scalar: true,
+        }
+    }
+}
+
+impl<'a> Broadcast<'a> for [Var<'a>] {
+    type Elem = Var<'a>;
+    fn column(&self) -> Column<'_, 'a> {
+        Column {
+            values: Values::Var(self),
+            scalar: false,
+        }
+    }
+}
+
+impl<'a, const N: usize> Broadcast<'a> for [Var<'a>; N] {
+    type Elem = Var<'a>;
+    fn column(&self) -> Column<'_, 'a> {
+        self[..].column()
+    }
+}
+
+impl<'a> Broadcast<'a> for Vec<Var<'a>> {
+    type Elem = Var<'a>;
+    fn column(&self) -> Column<'_, 'a> {
+        self[..].column()
+    }
+}
+
+impl<'a, T: Broadcast<'a> + ?Sized> Broadcast<'a> for &T {
+    type Elem = T::Elem;
+    fn column(&self) -> Column<'_, 'a> {
+        (**self).column()
+    }
+}
+
+/// The common type of the elements of some arguments, which is `Var` if any of them is a `Var` and
+/// `f64` otherwise. See `Promote`.
+pub trait Common<'a> {
+    type Output: Fuse<'a>;
+}
+
+impl<'a, A: Promote<B>, B> Common<'a> for (A, B)
+where
+    A::Output: Fuse<'a>,
+{
+    type Output = A::Output;
+}
+
+impl<'a, A: Promote<B>, B, C> Common<'a> for (A, B, C)
+where
+    A::Output: Promote<C>,
+    <A::Output as Promote<C>>::Output: Fuse<'a>,
+{
+    type Output = <A::Output as Promote<C>>::Output;
+}
+
+/// The type of the log density of a vectorized distribution with arguments `A` and `B` (and `C`).
+type Output2<'a, A, B> =
+    <(<A as Broadcast<'a>>::Elem, <B as Broadcast<'a>>::Elem) as Common<'a>>::Output;
+type Output3<'a, A, B, C> = <(
+    <A as Broadcast<'a>>::Elem,
+    <B as Broadcast<'a>>::Elem,
+    <C as Broadcast<'a>>::Elem,
+) as Common<'a>>::Output;
+
+/// The number of observations of some arguments, i.e. the common length of those that are not
+/// scalars, or 1 if they all are.
+///
+/// Panics if the arguments that are not scalars differ in length.
+fn observations(columns: &[Column]) -> usize {
+    let mut lengths = columns.iter().filter(|c| !c.scalar).map(|c| c.len());
+    let n = lengths.next().unwrap_or(1);
+    assert!(
+        lengths.all(|len| len == n),
+        "Vectorized arguments must have the same length."
+    );
+    n
+}
+
+/// Evaluates `log_density` at each observation of `columns`, where `log_density` takes the values
+/// of the arguments for one observation. This splits a vectorized statement into pointwise log
+/// likelihoods, as done by the `@pointwise` arm of the vectorized macros, e.g.
+/// `normal_lpdf!(@pointwise y; mu, sigma)`.
+///
+/// Panics if the arguments that are not scalars differ in length.
+pub fn pointwise<const N: usize>(
+    columns: [Column; N],
+    log_density: impl Fn([f64; N]) -> f64,
+) -> Vec<f64> {
+    (0..observations(&columns))
+        .map(|i| log_density(std::array::from_fn(|j| columns[j].value(i))))
+        .collect()
+}
+
+/// Sums `log_density` over the observations, where `log_density` takes the values of the
+/// arguments for one observation and returns the log density along with its partial derivatives
+/// with respect to each argument.
+///
+/// Panics if the arguments that are not scalars differ in length.
+fn sum<'a, T: Fuse<'a>, const N: usize>(
+    columns: [Column<'_, 'a>; N],
+    log_density: impl Fn([f64; N]) -> (f64, [f64; N]),
+) -> T {
+    let n = observations(&columns);
+
+    // the partial derivatives with respect to each `Var`, with broadcast ones accumulated
+    let mut grads: Vec<Vec<f64>> = columns
+        .iter()
+        .map(|c| match c.values {
+            Values::Var(v) => vec![0.; v.len()],
+            _ => vec![],
+        })
+        .collect();
+
+    let mut total = 0.;
+    for i in 0..n {
+        let (value, partials) = log_density(std::array::from_fn(|j| columns[j].value(i)));
+        total += value;
+        if total == f64::NEG_INFINITY {
+            break;
+        }
+        for (j, column) in columns.iter().enumerate() {
+            if let Some(grad) = grads[j].get_mut(column.index(i)) {
+                *grad += partials[j];
+            }
+        }
+    }
+
+    // the gradient is meaningless at -inf, and samplers reject such values anyway
+    let finite = total > f64::NEG_INFINITY;
+    let partials: Vec<(Var<'a>, f64)> = columns
+        .iter()
+        .zip(&grads)
+        .filter_map(|(column, grads)| match column.values {
+            Values::Var(v) => Some(v.iter().zip(grads)),
+            _ => None,
+        })
+        .flatten()
+        .map(|(&var, &grad)| (var, if finite { grad } else { 0. }))
+        .collect();
+    T::fuse(total, &partials)
+}
+
+/// The log density of the normal distribution, summed over `x`. See `Normal`.
+pub fn normal<'a, X, M, S>(x: &X, mu: &M, sigma: &S, normalized: bool) -> Output3<'a, X, M, S>
+where
+    X: Broadcast<'a> + ?Sized,
+    M: Broadcast<'a> + ?Sized,
+    S: Broadcast<'a> + ?Sized,
+    (X::Elem, M::Elem, S::Elem): Common<'a>,
+{
+    let constant = if normalized {
+        -0.5 * (2. * PI).ln()
+    } else {
+        0.
+    };
+    sum(
+        [x.column(), mu.column(), sigma.column()],
+        |[x, mu, sigma]| {
+            if sigma <= 0. {
+                return (f64::NEG_INFINITY, [0.; 3]);
+            }
+            let z = (x - mu) / sigma;
+            (
+                -sigma.ln() - 0.5 * z * z + constant,
+                [-z / sigma, z / sigma, (z * z - 1.) / sigma],
+            )
+        },
+    )
+}
+
+/// The log density of the log-normal distribution, summed over `x`. See `Lognormal`.
+pub fn lognormal<'a, X, M, S>(x: &X, mu: &M, sigma: &S, normalized: bool) -> Output3<'a, X, M, S>
+where
+    X: Broadcast<'a> + ?Sized,
+    M: Broadcast<'a> + ?Sized,
+    S: Broadcast<'a> + ?Sized,
+    (X::Elem, M::Elem, S::Elem): Common<'a>,
+{
+    let constant = if normalized {
+        -0.5 * (2. * PI).ln()
+    } else {
+        0.
+    };
+    sum(
+        [x.column(), mu.column(), sigma.column()],
+        |[x, mu, sigma]| {
+            if sigma <= 0. || x <= 0. {
+                return (f64::NEG_INFINITY, [0.; 3]);
+            }
+            let z = (x.ln() - mu) / sigma;
+            (
+                -0.5 * z * z - x.ln() - sigma.ln() + constant,
+                [-(z / sigma + 1.) / x, z / sigma, (z * z - 1.) / sigma],
+            )
+        },
+    )
+}
+
+/// The log density of the exponential distribution, summed over `x`. See `Exponential`.
+pub fn exponential<'a, X, R>(x: &X, rate: &R) -> Output2<'a, X, R>
+where
+    X: Broadcast<'a> + ?Sized,
+    R: Broadcast<'a> + ?Sized,
+    (X::Elem, R::Elem): Common<'a>,
+{
+    sum([x.column(), rate.column()], |[x, rate]| {
+        if rate <= 0. || x < 0. {
+            return (f64::NEG_INFINITY, [0.; 2]);
+        }
+        (rate.ln() - rate * x, [-rate, 1. / rate - x])
+    })
+}
+
+/// The log density of the Laplace distribution, summed over `x`. See `Laplace`.
+pub fn laplace<'a, X, M, B>(x: &X, mu: &M, b: &B, normalized: bool) -> Output3<'a, X, M, B>
+where
+    X: Broadcast<'a> + ?Sized,
+    M: Broadcast<'a> + ?Sized,
+    B: Broadcast<'a> + ?Sized,
+    (X::Elem, M::Elem, B::Elem): Common<'a>,
+{
+    let constant = if normalized { -LN_2 } else { 0. };
+    sum([x.column(), mu.column(), b.column()], |[x, mu, b]| {
+        if b <= 0. {
+            return (f64::NEG_INFINITY, [0.; 3]);
+        }
+        let d = x - mu;
+        let sign = if d > 0. {
+            1.
+        } else if d < 0. {
+            -1.
+        } else {
+            0.
+        };
+        (
+            -b.ln() - d.abs() / b + constant,
+            [-sign / b, sign / b, (d.abs() / b - 1.) / b],
+        )
+    })
+}
+
+/// The log density of the Cauchy distribution, summed over `x`. See `Cauchy`.
+pub fn cauchy<'a, X, M, S>(x: &X, mu: &M, sigma: &S, normalized: bool) -> Output3<'a, X, M, S>
+where
+    X: Broadcast<'a> + ?Sized,
+    M: Broadcast<'a> + ?Sized,
+    S: Broadcast<'a> + ?Sized,
+    (X::Elem, M::Elem, S::Elem): Common<'a>,
+{
+    let constant = if normalized { -PI.ln() } else { 0. };
+    sum(
+        [x.column(), mu.column(), sigma.column()],
+        |[x, mu, sigma]| {
+            if sigma <= 0. {
+                return (f64::NEG_INFINITY, [0.; 3]);
+            }
+            let d = x - mu;
+            let denominator = sigma * sigma + d * d;
+            (
+                (sigma / denominator).ln() + constant,
+                [
+                    -2. * d / denominator,
+                    2. * d / denominator,
+                    1. / sigma - 2. * sigma / denominator,
+                ],
+            )
+        },
+    )
+}
+
+/// The log mass of the Bernoulli distribution, summed over `k`. See `Bernoulli`.
+pub fn bernoulli<'a, K, P>(k: &K, theta: &P) -> Output2<'a, K, P>
+where
+    K: Broadcast<'a> + ?Sized,
+    P: Broadcast<'a> + ?Sized,
+    (K::Elem, P::Elem): Common<'a>,
+{
+    sum([k.column(), theta.column()], |[k, theta]| {
+        if !(0. ..=1.).contains(&theta) || k.fract() != 0. {
+            return (f64::NEG_INFINITY, [0.; 2]);
+        }
+        match k as i64 {
+            0 => ((1. - theta).ln(), [0., -1. / (1. - theta)]),
+            1 => (theta.ln(), [0., 1. / theta]),
+            _ => (f64::NEG_INFINITY, [0.; 2]),
+        }
+    })
+}
+
+/// The log mass of the binomial distribution, summed over `k`. See `Binomial`.
+pub fn binomial<'a, K, N, P>(k: &K, n: &N, theta: &P, normalized: bool) -> Output3<'a, K, N, P>
+where
+    K: Broadcast<'a> + ?Sized,
+    N: Broadcast<'a> + ?Sized,
+    P: Broadcast<'a> + ?Sized,
+    (K::Elem, N::Elem, P::Elem): Common<'a>,
+{
+    sum([k.column(), n.column(), theta.column()], |[k, n, theta]| {
+        if k.fract() != 0. || n.fract() != 0. {
+            return (f64::NEG_INFINITY, [0.; 3]);
+        }
+        let (k, n) = (k as i64, n as i64);
+        if !(0. ..=1.).contains(&theta) || n < 0 || k < 0 || k > n {
+            return (f64::NEG_INFINITY, [0.; 3]);
+        }
+        let constant = if normalized {
+            functions::lchoose(n as f64, k as f64)
+        } else {
+            0.
+        };
+        // skip the terms with no counts, which would be 0 * -inf when theta is 0 or 1
+        let (successes, failures) = (k as f64, (n - k) as f64);
+        let (mut value, mut grad) = (constant, 0.);
+        if k > 0 {
+            value += successes * theta.ln();
+            grad += successes / theta;
+        }
+        if n > k {
+            value += failures * (1. - theta).ln();
+            grad -= failures / (1. - theta);
+        }
+        (value, [0., 0., grad])
+    })
+}
+
+/// The log mass of the Poisson distribution, summed over `k`. See `Poisson`.
+pub fn poisson<'a, K, L>(k: &K, lambda: &L, normalized: bool) -> Output2<'a, K, L>
+where
+    K: Broadcast<'a> + ?Sized,
+    L: Broadcast<'a> + ?Sized,
+    (K::Elem, L::Elem): Common<'a>,
+{
+    sum([k.column(), lambda.column()], |[k, lambda]| {
+        if k.fract() != 0. {
+            return (f64::NEG_INFINITY, [0.; 2]);
+        }
+        let k = k as i64;
+        if lambda <= 0. || k < 0 {
+            return (f64::NEG_INFINITY, [0.; 2]);
+        }
+        let constant = if normalized { -ln_factorial(k) } else { 0. };
+        (
+            k as f64 * lambda.ln() - lambda + constant,
+            [0., k as f64 / lambda - 1.],
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::distributions::{Binomial, Distribution, Normal, Poisson};
+
+    #[test]
+    fn pointwise_statements() {
+        let y = [0.5, -1., 2.];
+        let mu = [0., 1., 1.5];
+        let pointwise = crate::normal_lpdf!(@pointwise y; mu, 2_f64);
+        assert_eq!(pointwise.len(), y.len());
+        for i in 0..y.len() {
+            let expected = Normal::new(mu[i], 2.).log_density(y[i]);
+            assert!((pointwise[i] - expected).abs() < 1e-12);
+        }
+        assert!((pointwise.iter().sum::<f64>() - crate::normal_lpdf!(y; mu, 2_f64)).abs() < 1e-12);
+
+        let k = vec![0_i64, 3, 7];
+        let pointwise = crate::poisson_lpmf!(@pointwise k; 2.5_f64);
+        assert_eq!(pointwise.len(), k.len());
+        assert!((pointwise[1] - Poisson::new(2.5).log_density(3)).abs() < 1e-12);
+
+        // truncated statements are not vectorized
+        let pointwise = crate::normal_lpdf!(@pointwise 0.5_f64; 0_f64, 1_f64; T[0., ]);
+        assert_eq!(pointwise.len(), 1);
+    }
+
+    #[test]
+    fn bernoulli_fractional_outcome() {
+        assert!((bernoulli(&[1., 0.], &0.3_f64) - 0.3_f64.ln() - 0.7_f64.ln()).abs() < 1e-12);
+        assert_eq!(bernoulli(&[1., 0.7], &0.3_f64), f64::NEG_INFINITY);
+    }
+
+    #[test]
+    fn binomial_fractional_counts() {
+        let expected = Binomial::new(5, 0.3).log_density(2);
+        assert!((binomial(&2_f64, &5_f64, &0.3_f64, true) - expected).abs() < 1e-12);
+        assert_eq!(
+            binomial(&2.5_f64, &5_f64, &0.3_f64, true),
+            f64::NEG_INFINITY
+        );
+        assert_eq!(
+            binomial(&2_f64, &5.5_f64, &0.3_f64, true),
+            f64::NEG_INFINITY
+        );
+    }
+
+    #[test]
+    fn poisson_fractional_count() {
+        let expected = Poisson::new(2.5).log_density(3);
+        assert!((poisson(&3_f64, &2.5_f64, true) - expected).abs() < 1e-12);
+        assert_eq!(poisson(&[3., 2.5], &2.5_f64, true), f64::NEG_INFINITY);
+    }
+}
@@ -1,5 +1,6 @@
 #![feature(generic_associated_types)]
 
+pub mod comparison;
 pub mod distributions;
 pub mod functions;
 pub mod samplers;
@@ -14,6 +14,38 @@ pub trait Sampler<V> {
     where
         F: Fn(&[V], S) -> V + Copy + Send + Sync,
         S: Send + Sync + Copy;
+    /// Get n_samples samples, followed by the generated quantities `g` evaluated at each sample.
+    ///
+    /// Like `sample`, the result has one row per dimension, so the generated quantities come
+    /// after the parameters.
+    fn sample_generated<'a, F, G, S>(
+        &self,
+        f: F,
+        g: G,
+        inits: &[V],
+        data: S,
+        n_samples: usize,
+    ) -> Vec<Vec<f64>>
+    where
+        F: Fn(&[V], S) -> V + Copy + Send + Sync,
+        G: Fn(&[f64], S) -> Vec<f64>,
+        S: Send + Sync + Copy,
+    {
+        let mut samples = self.sample(f, inits, data, n_samples);
+        let n_draws = samples.first().map_or(0, |x| x.len());
+
+        let generated = (0..n_draws)
+            .map(|i| g(&samples.iter().map(|x| x[i]).collect::<Vec<_>>(), data))
+            .collect::<Vec<_>>();
+
+        if let Some(first) = generated.first() {
+            for j in 0..first.len() {
+                samples.push(generated.iter().map(|x| x[j]).collect());
+            }
+        }
+
+        samples
+    }
     // fn sample_par<'a, F, S>(
     //     &self,
     //     f: F,
@@ -1,28 +1,566 @@
 use proc_macro::TokenStream;
-use quote::ToTokens;
-use syn::{parse_macro_input, parse_quote, Stmt};
-
-const DISTRIBUTIONS: [&str; 13] = [
-    "lognormal",
-    "normal!",
-    "exponential!",
-    "gamma!",
-    "uniform!",
-    "laplace!",
-    "beta!",
-    "bernoulli!",
-    "binomial!",
-    "poisson!",
-    "cauchy!",
-    "rayleigh",
-    "pareto!",
+use proc_macro2::TokenTree;
+use quote::{quote, ToTokens};
+use std::collections::HashSet;
+use syn::{
+    parse::Parser, parse_macro_input, parse_quote, punctuated::Punctuated, visit_mut::VisitMut,
+    Expr, Item, Stmt,
+};
+
+/// The built-in distributions, each with its normalized variant.
+const DISTRIBUTIONS: &[(&str, &str)] = &[
+    ("lognormal", "lognormal_lpdf"),
+    ("normal", "normal_lpdf"),
+    ("exponential", "exponential_lpdf"),
+    ("gamma", "gamma_lpdf"),
+    ("uniform", "uniform_lpdf"),
+    ("laplace", "laplace_lpdf"),
+    ("beta", "beta_lpdf"),
+    ("bernoulli", "bernoulli_lpmf"),
+    ("binomial", "binomial_lpmf"),
+    ("poisson", "poisson_lpmf"),
+    ("neg_binomial", "neg_binomial_lpmf"),
+    ("beta_binomial", "beta_binomial_lpmf"),
+    ("geometric", "geometric_lpmf"),
+    ("hypergeometric", "hypergeometric_lpmf"),
+    ("zero_inflated_poisson", "zero_inflated_poisson_lpmf"),
+    (
+        "zero_inflated_neg_binomial",
+        "zero_inflated_neg_binomial_lpmf",
+    ),
+    ("hurdle_poisson", "hurdle_poisson_lpmf"),
+    ("hurdle_neg_binomial", "hurdle_neg_binomial_lpmf"),
+    ("cauchy", "cauchy_lpdf"),
+    ("rayleigh", "rayleigh_lpdf"),
+    ("pareto", "pareto_lpdf"),
+    ("student_t", "student_t_lpdf"),
+    ("half_normal", "half_normal_lpdf"),
+    ("half_cauchy", "half_cauchy_lpdf"),
+    ("half_student_t", "half_student_t_lpdf"),
+    ("inv_gamma", "inv_gamma_lpdf"),
+    ("weibull", "weibull_lpdf"),
+    ("gumbel", "gumbel_lpdf"),
+    ("logistic", "logistic_lpdf"),
+    ("skew_normal", "skew_normal_lpdf"),
+    ("exp_mod_normal", "exp_mod_normal_lpdf"),
+    ("von_mises", "von_mises_lpdf"),
+    ("wrapped_normal", "wrapped_normal_lpdf"),
+    ("wrapped_cauchy", "wrapped_cauchy_lpdf"),
+    ("multi_normal", "multi_normal_lpdf"),
+    ("multi_normal_prec", "multi_normal_prec_lpdf"),
+    ("multi_normal_cholesky", "multi_normal_cholesky_lpdf"),
+    ("categorical", "categorical_lpmf"),
+    ("categorical_logit", "categorical_logit_lpmf"),
+    ("ordered_logistic", "ordered_logistic_lpmf"),
+    ("ordered_probit", "ordered_probit_lpmf"),
+    ("multinomial", "multinomial_lpmf"),
+    ("dirichlet", "dirichlet_lpdf"),
+    ("lkj_corr", "lkj_corr_lpdf"),
+    ("lkj_corr_cholesky", "lkj_corr_cholesky_lpdf"),
+    ("wishart", "wishart_lpdf"),
+    ("inv_wishart", "inv_wishart_lpdf"),
+    ("von_mises_fisher", "von_mises_fisher_lpdf"),
+];
+
+/// The built-in distributions that are vectorized, by their normalized variants. Their statements
+/// are split into one pointwise log likelihood per observation.
+const VECTORIZED: &[&str] = &[
+    "normal_lpdf",
+    "lognormal_lpdf",
+    "exponential_lpdf",
+    "laplace_lpdf",
+    "cauchy_lpdf",
+    "bernoulli_lpmf",
+    "binomial_lpmf",
+    "poisson_lpmf",
 ];
 
+/// Which function is being generated from the body of a model.
+#[derive(Clone, Copy, PartialEq)]
+enum Mode {
+    /// The log density, accumulating every distribution statement into `target`.
+    Density,
+    /// The log density of the prior statements only.
+    Prior,
+    /// The log density of the likelihood statements only.
+    Likelihood,
+    /// The likelihood statements, each pushed separately onto `pointwise`.
+    Pointwise,
+    /// The generated quantities, dropping every distribution statement.
+    Generated,
+}
+
+/// Which part of the log density a statement belongs to.
+#[derive(Clone, Copy, PartialEq)]
+enum Part {
+    Prior,
+    Likelihood,
+}
+
+/// Checks whether any of the identifiers in `tokens` is in `names`.
+fn mentions(tokens: proc_macro2::TokenStream, names: &HashSet<String>) -> bool {
+    tokens.into_iter().any(|token| match token {
+        TokenTree::Ident(ident) => names.contains(&ident.to_string()),
+        TokenTree::Group(group) => mentions(group.stream(), names),
+        _ => false,
+    })
+}
+
+/// Collects the names bound by the pattern `pat` into `names`, e.g. `a` and `b` for `(a, [b, _])`
+/// or `a` for `a: f64`.
+fn pattern_names(pat: &syn::Pat, names: &mut Vec<String>) {
+    match pat {
+        syn::Pat::Ident(p) => {
+            names.push(p.ident.to_string());
+            if let Some((_, subpat)) = &p.subpat {
+                pattern_names(subpat, names);
+            }
+        }
+        syn::Pat::Type(p) => pattern_names(&p.pat, names),
+        syn::Pat::Reference(p) => pattern_names(&p.pat, names),
+        syn::Pat::Box(p) => pattern_names(&p.pat, names),
+        syn::Pat::Tuple(p) => p.elems.iter().for_each(|p| pattern_names(p, names)),
+        syn::Pat::TupleStruct(p) => p.pat.elems.iter().for_each(|p| pattern_names(p, names)),
+        syn::Pat::Slice(p) => p.elems.iter().for_each(|p| pattern_names(p, names)),
+        syn::Pat::Struct(p) => p.fields.iter().for_each(|f| pattern_names(&f.pat, names)),
+        syn::Pat::Or(p) => p.cases.iter().for_each(|p| pattern_names(p, names)),
+        _ => {}
+    }
+}
+
+/// Rewrites the body of a model. In `Mode::Density`, every distribution statement and every
+/// `target += ...` statement is accumulated into the `target` log density.
+struct Rewrite {
+    mode: Mode,
+    /// User-registered distributions, in addition to the built-in `DISTRIBUTIONS`.
+    distributions: Vec<String>,
+    /// Variables in scope that are derived from the parameters, i.e. the parameter slice itself
+    /// and every variable bound from an expression mentioning a parameter. Loop indices over
+    /// ranges are never derived from the parameters, even in `for i in 0..params.len()`.
+    parameters: HashSet<String>,
+    /// The part given by an enclosing `prior!` or `likelihood!` block.
+    tag: Option<Part>,
+    /// Whether a `generated_quantities!` statement was found.
+    generated: bool,
+    /// Whether the model uses `Var`, whose values are converted to `f64` by the companion
+    /// functions.
+    var: bool,
+}
+
+impl Rewrite {
+    /// Creates a rewrite of a model whose parameter slice is bound by `parameters`.
+    fn new(mode: Mode, distributions: &[String], parameters: &syn::Pat, var: bool) -> Self {
+        let mut names = Vec::new();
+        pattern_names(parameters, &mut names);
+        Self {
+            mode,
+            distributions: distributions.to_vec(),
+            parameters: names.into_iter().collect(),
+            tag: None,
+            generated: false,
+            var,
+        }
+    }
+
+    /// Converts `expr` to an `f64`.
+    fn value(&self, expr: &Expr) -> Expr {
+        if self.var {
+            parse_quote!(::talos::functions::Real::value(&(#expr)))
+        } else {
+            expr.clone()
+        }
+    }
+
+    fn is_distribution(&self, mac: &syn::Macro) -> bool {
+        mac.path.get_ident().is_some_and(|ident| {
+            let name = ident.to_string();
+            DISTRIBUTIONS
+                .iter()
+                .any(|(unnormalized, normalized)| name == *unnormalized || name == *normalized)
+                || self.distributions.contains(&name)
+        })
+    }
+
+    /// Replaces a built-in distribution by its normalized variant.
+    fn normalize(mac: &syn::Macro) -> syn::Macro {
+        let mut mac = mac.clone();
+        if let Some((_, normalized)) = DISTRIBUTIONS
+            .iter()
+            .find(|(unnormalized, _)| mac.path.is_ident(unnormalized))
+        {
+            mac.path = syn::Ident::new(normalized, proc_macro2::Span::call_site()).into();
+        }
+        mac
+    }
+
+    fn is_target(expr: &Expr) -> bool {
+        match expr {
+            Expr::Path(p) => p.path.is_ident("target"),
+            _ => false,
+        }
+    }
+
+    fn statement_macro(stmt: &Stmt) -> Option<&syn::Macro> {
+        match stmt {
+            Stmt::Item(Item::Macro(m)) if m.ident.is_none() => Some(&m.mac),
+            Stmt::Semi(Expr::Macro(m), _) | Stmt::Expr(Expr::Macro(m)) => Some(&m.mac),
+            _ => None,
+        }
+    }
+
+    /// Classifies a distribution statement as a prior if its variate (everything before the `;`)
+    /// mentions a parameter, and as a likelihood otherwise.
+    fn classify(&self, mac: &syn::Macro) -> Part {
+        let variate = mac
+            .tokens
+            .clone()
+            .into_iter()
+            .take_while(|t| !matches!(t, TokenTree::Punct(p) if p.as_char() == ';'))
+            .collect();
+        if mentions(variate, &self.parameters) {
+            Part::Prior
+        } else {
+            Part::Likelihood
+        }
+    }
+
+    /// Returns the right-hand side of a `target += ...` statement.
+    fn target_term(stmt: &Stmt) -> Option<&Expr> {
+        match stmt {
+            Stmt::Semi(Expr::AssignOp(a), _) | Stmt::Expr(Expr::AssignOp(a))
+                if Self::is_target(&a.left) && matches!(a.op, syn::BinOp::AddEq(_)) =>
+            {
+                Some(&a.right)
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the term that `stmt` adds to the log density and the part it belongs to, if any.
+    /// Untagged `target += ...` statements belong to the prior, as they are usually Jacobian
+    /// adjustments.
+    fn term(&self, stmt: &Stmt) -> Option<(Expr, Part)> {
+        match Self::target_term(stmt) {
+            Some(term) => Some((parse_quote!((#term)), self.tag.unwrap_or(Part::Prior))),
+            None => Self::statement_macro(stmt)
+                .filter(|mac| self.is_distribution(mac))
+                .map(|mac| {
+                    let part = self.tag.unwrap_or_else(|| self.classify(mac));
+                    let mac = if self.mode == Mode::Pointwise {
+                        Self::normalize(mac)
+                    } else {
+                        mac.clone()
+                    };
+                    (parse_quote!(#mac), part)
+                }),
+        }
+    }
+
+    /// Marks the names bound by `pat` as derived from the parameters if `derived`, and otherwise
+    /// as shadowing any such variable.
+    fn bind(&mut self, pat: &syn::Pat, derived: bool) {
+        let mut names = Vec::new();
+        pattern_names(pat, &mut names);
+        for name in names {
+            if derived {
+                self.parameters.insert(name);
+            } else {
+                self.parameters.remove(&name);
+            }
+        }
+    }
+
+    /// Returns the statements that replace `stmt`.
+    fn rewrite(&mut self, mut stmt: Stmt) -> Vec<Stmt> {
+        if let Some((term, part)) = self.term(&stmt) {
+            let keep = match self.mode {
+                Mode::Density => true,
+                Mode::Prior => part == Part::Prior,
+                Mode::Likelihood | Mode::Pointwise => part == Part::Likelihood,
+                Mode::Generated => false,
+            };
+            return match (keep, self.mode) {
+                (false, _) => vec![],
+                (true, Mode::Pointwise) if Self::target_term(&stmt).is_some() => {
+                    let error = syn::Error::new_spanned(
+                        &stmt,
+                        "`target += ...` cannot be split into pointwise log likelihoods. Write the \
+                         likelihood as distribution statements, or move this statement out of \
+                         `likelihood! { ... }`.",
+                    )
+                    .to_compile_error();
+                    vec![parse_quote!(#error;)]
+                }
+                (true, Mode::Pointwise) => match term {
+                    Expr::Macro(m) if VECTORIZED.iter().any(|name| m.mac.path.is_ident(name)) => {
+                        let mut mac = m.mac;
+                        let tokens = mac.tokens;
+                        mac.tokens = quote!(@pointwise #tokens);
+                        vec![parse_quote! {
+                            pointwise.extend(#mac);
+                        }]
+                    }
+                    term => {
+                        let term = self.value(&term);
+                        vec![parse_quote! {
+                            pointwise.push(#term);
+                        }]
+                    }
+                },
+                (true, _) => vec![parse_quote! {
+                    target = target + #term;
+                }],
+            };
+        }
+        if let Some(mac) = Self::statement_macro(&stmt) {
+            if mac.path.is_ident("generated_quantities") {
+                self.generated = true;
+                let quantities = Punctuated::<Expr, syn::Token![,]>::parse_terminated
+                    .parse2(mac.tokens.clone())
+                    .unwrap_or_else(|e| panic!("Failed to parse generated quantities: {}", e))
+                    .iter()
+                    .map(|q| self.value(q))
+                    .collect::<Vec<_>>();
+                return match self.mode {
+                    Mode::Generated => vec![parse_quote! {
+                        return vec![#(#quantities),*];
+                    }],
+                    _ => vec![],
+                };
+            }
+            let tag = if mac.path.is_ident("prior") {
+                Some(Part::Prior)
+            } else if mac.path.is_ident("likelihood") {
+                Some(Part::Likelihood)
+            } else {
+                None
+            };
+            if tag.is_some() {
+                let stmts = syn::Block::parse_within
+                    .parse2(mac.tokens.clone())
+                    .unwrap_or_else(|e| panic!("Failed to parse tagged statements: {}", e));
+                let outer = std::mem::replace(&mut self.tag, tag);
+                let stmts = stmts.into_iter().flat_map(|s| self.rewrite(s)).collect();
+                self.tag = outer;
+                return stmts;
+            }
+        }
+        syn::visit_mut::visit_stmt_mut(self, &mut stmt);
+        vec![stmt]
+    }
+}
+
+impl VisitMut for Rewrite {
+    fn visit_block_mut(&mut self, block: &mut syn::Block) {
+        let outer = self.parameters.clone();
+        block.stmts = std::mem::take(&mut block.stmts)
+            .into_iter()
+            .flat_map(|stmt| self.rewrite(stmt))
+            .collect();
+        self.parameters = outer;
+    }
+
+    fn visit_local_mut(&mut self, local: &mut syn::Local) {
+        syn::visit_mut::visit_local_mut(self, local);
+        let derived = local
+            .init
+            .as_ref()
+            .is_some_and(|(_, init)| mentions(init.to_token_stream(), &self.parameters));
+        self.bind(&local.pat, derived);
+    }
+
+    fn visit_expr_for_loop_mut(&mut self, for_loop: &mut syn::ExprForLoop) {
+        self.visit_expr_mut(&mut for_loop.expr);
+        let outer = self.parameters.clone();
+        let derived = mentions(for_loop.expr.to_token_stream(), &self.parameters);
+        match (&*for_loop.expr, &for_loop.pat) {
+            (Expr::Range(_), pat) => self.bind(pat, false),
+            // `for (i, x) in params.iter().enumerate()` only derives `x` from the parameters
+            (Expr::MethodCall(call), syn::Pat::Tuple(pat))
+                if call.method == "enumerate" && pat.elems.len() == 2 =>
+            {
+                self.bind(&pat.elems[0], false);
+                self.bind(&pat.elems[1], derived);
+            }
+            (_, pat) => self.bind(pat, derived),
+        }
+        self.visit_block_mut(&mut for_loop.body);
+        self.parameters = outer;
+    }
+}
+
+/// Copies the model into a new function named `<model>_<suffix>`.
+fn rename(model: &syn::ItemFn, suffix: &str) -> syn::ItemFn {
+    let mut f = model.clone();
+    f.attrs.push(parse_quote!(#[allow(unused)]));
+    f.sig.ident = syn::Ident::new(
+        &format!("{}_{}", model.sig.ident, suffix),
+        model.sig.ident.span(),
+    );
+    f
+}
+
+/// Creates a function named `<model>_<suffix>` which runs `stmts` and returns a `Vec<f64>`. It
+/// takes the parameters as a slice of `f64` even if the model uses `Var`: then `stmts` run in an
+/// inner function with the signature of the model, on parameters added to a new tape, so they can
+/// use the `Var` API as usual.
+fn companion(
+    model: &syn::ItemFn,
+    suffix: &str,
+    lifetime: Option<&str>,
+    stmts: Vec<Stmt>,
+) -> syn::ItemFn {
+    let mut f = rename(model, suffix);
+    f.sig.output = parse_quote!(-> Vec<f64>);
+    let lifetime = match lifetime {
+        Some(lifetime) => lifetime,
+        None => {
+            f.block.stmts = stmts;
+            return f;
+        }
+    };
+
+    let mut inner = model.clone();
+    inner.attrs.clear();
+    inner.sig.output = parse_quote!(-> Vec<f64>);
+    inner.block.stmts = stmts;
+
+    let mut args: Vec<Expr> = Vec::new();
+    for (i, fnarg) in f.sig.inputs.iter_mut().enumerate() {
+        if let syn::FnArg::Typed(pattype) = fnarg {
+            let arg = syn::Ident::new(&format!("arg{}", i), proc_macro2::Span::call_site());
+            *pattype.pat = parse_quote!(#arg);
+            if pattype.ty.to_token_stream().to_string() == format!("& [Var < {} >]", lifetime) {
+                *pattype.ty = parse_quote!(&[f64]);
+                args.push(parse_quote!(&tape.add_vars(#arg)));
+            } else {
+                args.push(parse_quote!(#arg));
+            }
+        }
+    }
+    let name = &model.sig.ident;
+    f.block.stmts = vec![
+        Stmt::Item(Item::Fn(inner)),
+        parse_quote!(let tape = ::reverse::Tape::new();),
+        Stmt::Expr(parse_quote!(#name(#(#args),*))),
+    ];
+    f
+}
+
+/// Turns a function into a log density that can be passed to a sampler.
+///
+/// The argument is either `"f64"` or `"Var<'a>"`, matching the type of the parameter slice. A
+/// `target` log density is created at the start of the function and returned at the end. Every
+/// distribution statement (e.g. `normal!(y; mu, sigma);`) is added to `target`, at any depth in
+/// the function body.
+///
+/// Arbitrary scalar terms, such as a Jacobian adjustment or a marginalized mixture, can be added
+/// with `target += expr;`. This works for both `f64` and `Var` models.
+///
+/// Two more log densities with the same signature are generated: `<name>_log_prior`, which only
+/// includes the prior statements, and `<name>_log_likelihood`, which only includes the likelihood
+/// statements. A distribution statement is a prior if its variate depends on the parameters (e.g.
+/// `normal!(mu; 0_f64, 10_f64)`), and a likelihood otherwise (e.g. `normal!(y[i]; mu, 1_f64)`).
+/// `target += ...` statements count towards the prior, since they are usually Jacobian
+/// adjustments for transformed parameters. Both rules can be overridden by wrapping statements in
+/// a `prior! { ... }` or `likelihood! { ... }` block, e.g. for a likelihood term that is not a
+/// distribution statement.
+///
+/// ```ignore
+/// #[model("f64")]
+/// fn lnlik(params: &[f64], data: &[f64]) {
+///     let (mu, log_sigma) = (params[0], params[1]);
+///     normal!(mu; 0_f64, 10_f64);
+///     // Jacobian of the log transform, which is part of the prior
+///     target += log_sigma;
+///     let sigma = log_sigma.exp();
+///     for y in data {
+///         // a normal contaminated by outliers
+///         likelihood! {
+///             let lps = [normal_lpdf!(*y; mu, sigma), cauchy_lpdf!(*y; mu, sigma)];
+///             target += log_mix(&[0.9_f64, 0.1], &lps);
+///         }
+///     }
+/// }
+///
+/// // lnlik(p, d) == lnlik_log_prior(p, d) + lnlik_log_likelihood(p, d)
+/// ```
+///
+/// With a `pointwise` argument, `<name>_pointwise_log_likelihood` is also generated. It takes the
+/// parameters as a slice of `f64` and returns the value of every likelihood statement separately,
+/// in the order they are run, so each observation gets its own log likelihood contribution.
+/// Built-in distributions are replaced by their normalized variants (e.g. `normal!` by
+/// `normal_lpdf!`), so the values are actual log likelihoods. A vectorized statement such as
+/// `normal!(y; mu, sigma)` contributes one value per element of `y`. Only distribution statements
+/// on the data can be split into observations: `target += ...` statements in the prior are
+/// omitted, and those in a `likelihood! { ... }` block are a compile error. See
+/// `talos::comparison::pointwise_log_likelihood` for evaluating it at every draw.
+///
+/// In a `Var` model, both of these functions add the parameters to a new tape and run the model
+/// body on them, so the body can use the `Var` API (and `target`) as in the log density.
+///
+/// ```ignore
+/// #[model("f64", pointwise)]
+/// fn lnlik(params: &[f64], data: &[f64]) {
+///     normal!(params[0]; 0_f64, 10_f64);
+///     for y in data {
+///         normal!(*y; params[0], 1_f64);
+///     }
+/// }
+///
+/// // lnlik_pointwise_log_likelihood(p, d).len() == d.len()
+/// ```
+///
+/// Derived quantities can be computed for each draw by ending the function with a
+/// `generated_quantities!(...)` statement listing `f64` (or `Var`) expressions. This generates a
+/// second function, `<name>_generated_quantities`, which takes the parameters as a slice of `f64`
+/// and returns the quantities as a `Vec<f64>`. It runs the model body without any of the
+/// distribution or `target` statements, and is meant to be passed to `Sampler::sample_generated`.
+/// Posterior predictive draws can be made with the `_rng` macros, such as `normal_rng!` below for a
+/// new observation at x = 10.
+///
+/// ```ignore
+/// #[model("f64")]
+/// fn lnlik(params: &[f64], data: &[&[f64]]) {
+///     let (m, b, s) = (params[0], params[1], params[2]);
+///     for i in 0..data[0].len() {
+///         normal!(data[1][i]; data[0][i] * m + b, s.exp());
+///     }
+///     generated_quantities!(s.exp(), m * 10. + b, normal_rng!(m * 10. + b, s.exp()));
+/// }
+/// ```
+///
+/// Log density macros defined outside of talos can be registered with a `distributions(...)`
+/// argument, after which their statements are accumulated like the built-in distributions.
+///
+/// ```ignore
+/// macro_rules! maxwell {
+///     ( $var: expr; $a: expr ) => {{
+///         2_f64 * $var.ln() - 3_f64 * $a.ln() - 0.5_f64 * ($var / $a).powi(2)
+///     }};
+/// }
+///
+/// #[model("f64", distributions(maxwell))]
+/// fn lnlik(params: &[f64], data: &[f64]) {
+///     maxwell!(params[1]; 1_f64);
+///     for y in data {
+///         normal!(*y; params[0], params[1]);
+///     }
+/// }
+/// ```
 #[proc_macro_attribute]
 pub fn model(args: TokenStream, item: TokenStream) -> TokenStream {
     let macro_args = parse_macro_input!(args as syn::AttributeArgs);
+    let input = parse_macro_input!(item as syn::ItemFn);
+    expand(&macro_args, input).into()
+}
 
-    assert!(macro_args.len() == 1, "Macro must only have one argument.");
+/// Generates the log density of `model` and its companion functions. See `model`.
+fn expand(macro_args: &[syn::NestedMeta], mut input: syn::ItemFn) -> proc_macro2::TokenStream {
+    assert!(
+        !macro_args.is_empty(),
+        "Macro must have the parameter type as its first argument."
+    );
 
     let macro_arg = match &macro_args[0] {
         syn::NestedMeta::Meta(_) => panic!("Put quotation marks around the argument."),
@@ -32,12 +570,38 @@ pub fn model(args: TokenStream, item: TokenStream) -> TokenStream {
         },
     };
 
+    let mut distributions = Vec::new();
+    let mut pointwise = false;
+
+    for arg in &macro_args[1..] {
+        match arg {
+            syn::NestedMeta::Meta(syn::Meta::List(list)) if list.path.is_ident("distributions") => {
+                for dist in list.nested.iter() {
+                    match dist {
+                        syn::NestedMeta::Meta(syn::Meta::Path(path))
+                            if path.get_ident().is_some() =>
+                        {
+                            distributions.push(path.to_token_stream().to_string())
+                        }
+                        _ => panic!("Distributions must be given as macro names without the `!`."),
+                    }
+                }
+            }
+            syn::NestedMeta::Meta(syn::Meta::Path(path)) if path.is_ident("pointwise") => {
+                pointwise = true
+            }
+            _ => panic!(
+                "Unknown argument. Only `distributions(...)` and `pointwise` may follow the parameter type."
+            ),
+        }
+    }
+
     assert!(
         (macro_arg.starts_with("Var")) || (macro_arg == "f64"),
         "Macro argument must be Var or f64."
     );
 
-    let mut input = parse_macro_input!(item as syn::ItemFn);
+    let model = input.clone();
 
     let mut param_arg_name = None;
 
@@ -50,8 +614,7 @@ pub fn model(args: TokenStream, item: TokenStream) -> TokenStream {
 
     let genericargs = generics
         .split(", ")
-        .into_iter()
-        .filter_map(|g| if g.starts_with("'") { Some(g) } else { None })
+        .filter(|g| g.starts_with('\''))
         .collect::<Vec<_>>();
 
     let add_target: Stmt = match genericargs.len() {
@@ -114,11 +677,13 @@ pub fn model(args: TokenStream, item: TokenStream) -> TokenStream {
                 );
             }
 
-            input.sig.output =
-                syn::parse_str(&format!("-> Var < {} >", genericargs[0])).expect(&format!(
-                    "Output type could not be rewritten to Var<{}>!",
-                    genericargs[0]
-                ));
+            input.sig.output = syn::parse_str(&format!("-> Var < {} >", genericargs[0]))
+                .unwrap_or_else(|_| {
+                    panic!(
+                        "Output type could not be rewritten to Var<{}>!",
+                        genericargs[0]
+                    )
+                });
 
             parse_quote! {
                 let mut target = #param_arg_name[0].tape.add_var(0.);
@@ -131,23 +696,309 @@ pub fn model(args: TokenStream, item: TokenStream) -> TokenStream {
         return target;
     };
 
-    input.block.stmts.insert(0, add_target);
-    input.block.stmts.push(return_target);
-
-    input.block.stmts = (input.block.stmts)
-        .iter()
-        .map(|s| {
-            let mut ss = s.to_token_stream().to_string();
-            for dist in DISTRIBUTIONS {
-                if ss.contains(dist) {
-                    // println!("{:?}", ss);
-                    ss = ss.replace(dist, &format!("target = target + {}", dist));
-                    break;
+    let parameters = param_arg_name
+        .expect("The parameter slice was found above.")
+        .clone();
+
+    let lifetime = genericargs.first().copied();
+
+    let mut output = proc_macro2::TokenStream::new();
+    let mut generated = false;
+
+    for (mode, suffix) in [
+        (Mode::Density, None),
+        (Mode::Prior, Some("log_prior")),
+        (Mode::Likelihood, Some("log_likelihood")),
+    ] {
+        let mut f = match suffix {
+            Some(suffix) => rename(&input, suffix),
+            None => input.clone(),
+        };
+        let mut rewrite = Rewrite::new(mode, &distributions, &parameters, lifetime.is_some());
+        rewrite.visit_block_mut(&mut f.block);
+        generated = rewrite.generated;
+        f.block.stmts.insert(0, add_target.clone());
+        f.block.stmts.push(return_target.clone());
+        f.to_tokens(&mut output);
+    }
+
+    if pointwise {
+        let mut block = model.block.clone();
+        Rewrite::new(
+            Mode::Pointwise,
+            &distributions,
+            &parameters,
+            lifetime.is_some(),
+        )
+        .visit_block_mut(&mut block);
+        let mut stmts = vec![
+            add_target.clone(),
+            parse_quote! {
+                let mut pointwise: Vec<f64> = Vec::new();
+            },
+        ];
+        stmts.extend(block.stmts);
+        stmts.push(parse_quote! {
+            return pointwise;
+        });
+        companion(&model, "pointwise_log_likelihood", lifetime, stmts).to_tokens(&mut output);
+    }
+
+    if generated {
+        let mut block = model.block.clone();
+        Rewrite::new(
+            Mode::Generated,
+            &distributions,
+            &parameters,
+            lifetime.is_some(),
+        )
+        .visit_block_mut(&mut block);
+        let mut stmts = vec![add_target.clone()];
+        stmts.extend(block.stmts);
+        companion(&model, "generated_quantities", lifetime, stmts).to_tokens(&mut output);
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use syn::punctuated::Punctuated;
+
+    /// Expands the model `item` with the arguments `args`, and returns the body of the generated
+    /// function `name`.
+    fn body(args: proc_macro2::TokenStream, item: proc_macro2::TokenStream, name: &str) -> String {
+        let args = Punctuated::<syn::NestedMeta, syn::Token![,]>::parse_terminated
+            .parse2(args)
+            .unwrap();
+        let output = expand(
+            &args.into_iter().collect::<Vec<_>>(),
+            syn::parse2(item).unwrap(),
+        );
+        let file: syn::File = syn::parse2(output).unwrap();
+        file.items
+            .into_iter()
+            .find_map(|item| match item {
+                Item::Fn(f) if f.sig.ident == name => Some(f.block.to_token_stream().to_string()),
+                _ => None,
+            })
+            .unwrap_or_else(|| panic!("No function named {}.", name))
+    }
+
+    /// Checks that the distribution statements `priors` are only accumulated into the log prior
+    /// of the model `item`, and `likelihoods` only into its log likelihood.
+    fn assert_parts(
+        item: proc_macro2::TokenStream,
+        priors: &[proc_macro2::TokenStream],
+        likelihoods: &[proc_macro2::TokenStream],
+    ) {
+        let prior = body(quote!("f64"), item.clone(), "lnlik_log_prior");
+        let likelihood = body(quote!("f64"), item, "lnlik_log_likelihood");
+        for (stmts, part, other) in [
+            (priors, &prior, &likelihood),
+            (likelihoods, &likelihood, &prior),
+        ] {
+            for stmt in stmts {
+                let stmt = quote!(target = target + #stmt;).to_string();
+                assert!(part.contains(&stmt), "{} is missing from {}", stmt, part);
+                assert!(!other.contains(&stmt), "{} is misplaced in {}", stmt, other);
+            }
+        }
+    }
+
+    #[test]
+    fn loop_indices_are_not_parameters() {
+        let item = quote! {
+            fn lnlik(params: &[f64], data: &[f64]) {
+                for i in 0..params.len() {
+                    normal!(params[i]; 0_f64, 1_f64);
+                    normal!(data[i]; params[i], 1_f64);
+                }
+                for (j, mu) in params.iter().enumerate() {
+                    normal!(*mu; 0_f64, 1_f64);
+                    normal!(data[j]; *mu, 1_f64);
                 }
             }
-            syn::parse_str(&ss).expect(&format!("failed to convert statement: {}", ss))
-        })
-        .collect::<Vec<_>>();
+        };
+        assert_parts(
+            item,
+            &[
+                quote!(normal!(params[i]; 0_f64, 1_f64)),
+                quote!(normal!(*mu; 0_f64, 1_f64)),
+            ],
+            &[
+                quote!(normal!(data[i]; params[i], 1_f64)),
+                quote!(normal!(data[j]; *mu, 1_f64)),
+            ],
+        );
+    }
 
-    input.into_token_stream().into()
+    #[test]
+    fn only_pattern_names_are_bound() {
+        let item = quote! {
+            fn lnlik(params: &[f64], data: &[i64]) {
+                let (mu, [sigma, _]): (f64, [f64; 2]) = (params[0], [params[1], 0.]);
+                normal!(mu; 0_f64, 1_f64);
+                normal!(data[0] as f64; mu, sigma);
+                poisson!(data[1]; sigma);
+            }
+        };
+        assert_parts(
+            item,
+            &[quote!(normal!(mu; 0_f64, 1_f64))],
+            &[
+                quote!(normal!(data[0] as f64; mu, sigma)),
+                quote!(poisson!(data[1]; sigma)),
+            ],
+        );
+    }
+
+    #[test]
+    fn shadowing() {
+        let item = quote! {
+            fn lnlik(params: &[f64], data: &[f64]) {
+                let x = params[0];
+                normal!(x; 0_f64, 1_f64);
+                {
+                    let x = data[0];
+                    normal!(x; params[0], 1_f64);
+                }
+                normal!(x * 2_f64; 0_f64, 1_f64);
+                let x = data[1];
+                normal!(x - 1_f64; params[0], 1_f64);
+                for params in data.chunks(2) {
+                    normal!(params[0]; 0_f64, 1_f64);
+                }
+            }
+        };
+        assert_parts(
+            item,
+            &[
+                quote!(normal!(x; 0_f64, 1_f64)),
+                quote!(normal!(x * 2_f64; 0_f64, 1_f64)),
+            ],
+            &[
+                quote!(normal!(x; params[0], 1_f64)),
+                quote!(normal!(x - 1_f64; params[0], 1_f64)),
+                quote!(normal!(params[0]; 0_f64, 1_f64)),
+            ],
+        );
+    }
+
+    #[test]
+    fn var_companions() {
+        let item = quote! {
+            fn lnlik<'a>(params: &[Var<'a>], data: &[f64]) {
+                let offset = params[0].tape.add_var(1.);
+                for y in data {
+                    normal!(*y; params[0] + offset, 1_f64);
+                }
+                generated_quantities!(params[0] + offset, 2_f64);
+            }
+        };
+        let args = quote!("Var<'a>", pointwise);
+        for name in [
+            "lnlik_pointwise_log_likelihood",
+            "lnlik_generated_quantities",
+        ] {
+            let companion = body(args.clone(), item.clone(), name);
+            let inner = quote!(
+                fn lnlik<'a>(params: &[Var<'a>], data: &[f64]) -> Vec<f64> {
+                    let mut target = params[0].tape.add_var(0.);
+                }
+            );
+            let inner = inner.to_string();
+            assert!(
+                companion.contains(&inner[..inner.len() - 2]),
+                "{}",
+                companion
+            );
+            let call = quote!(let tape = ::reverse::Tape::new(); lnlik(&tape.add_vars(arg0), arg1));
+            assert!(companion.contains(&call.to_string()), "{}", companion);
+        }
+        let generated = body(args, item, "lnlik_generated_quantities");
+        let quantities = quote!(return vec![
+            ::talos::functions::Real::value(&(params[0] + offset)),
+            ::talos::functions::Real::value(&(2_f64))
+        ];);
+        assert!(generated.contains(&quantities.to_string()), "{}", generated);
+    }
+
+    #[test]
+    fn untagged_target_is_prior() {
+        let item = quote! {
+            fn lnlik(params: &[f64], data: &[f64]) {
+                let log_sigma = params[1];
+                target += log_sigma;
+                likelihood! {
+                    target += data[0];
+                }
+            }
+        };
+        let jacobian = quote!(target = target + (log_sigma);).to_string();
+        let term = quote!(target = target + (data[0]);).to_string();
+
+        let prior = body(quote!("f64"), item.clone(), "lnlik_log_prior");
+        assert!(prior.contains(&jacobian) && !prior.contains(&term));
+        let likelihood = body(quote!("f64"), item.clone(), "lnlik_log_likelihood");
+        assert!(likelihood.contains(&term) && !likelihood.contains(&jacobian));
+        let density = body(quote!("f64"), item, "lnlik");
+        assert!(density.contains(&term) && density.contains(&jacobian));
+    }
+
+    #[test]
+    fn pointwise_statements() {
+        let item = quote! {
+            fn lnlik(params: &[f64], data: &[f64]) {
+                normal!(params[0]; 0_f64, 1_f64);
+                normal!(data; params[0], 1_f64);
+                student_t!(data[0]; 3_f64, params[0], 1_f64);
+            }
+        };
+        let pointwise = body(
+            quote!("f64", pointwise),
+            item,
+            "lnlik_pointwise_log_likelihood",
+        );
+        let vectorized = quote!(pointwise.extend(normal_lpdf!(@pointwise data; params[0], 1_f64)););
+        let scalar = quote!(pointwise.push(student_t_lpdf!(data[0]; 3_f64, params[0], 1_f64)););
+        assert!(pointwise.contains(&vectorized.to_string()), "{}", pointwise);
+        assert!(pointwise.contains(&scalar.to_string()), "{}", pointwise);
+        assert!(!pointwise.contains("0_f64 , 1_f64"), "{}", pointwise);
+    }
+
+    #[test]
+    fn pointwise_target() {
+        let item = quote! {
+            fn lnlik(params: &[f64], data: &[f64]) {
+                target += params[1];
+                for y in data {
+                    normal!(*y; params[0], 1_f64);
+                }
+            }
+        };
+        let pointwise = body(
+            quote!("f64", pointwise),
+            item,
+            "lnlik_pointwise_log_likelihood",
+        );
+        assert!(!pointwise.contains("target = target"));
+        let statement = quote!(pointwise.extend(normal_lpdf!(@pointwise *y; params[0], 1_f64)););
+        assert!(pointwise.contains(&statement.to_string()));
+
+        let item = quote! {
+            fn lnlik(params: &[f64], data: &[f64]) {
+                likelihood! {
+                    target += data[0] * params[0];
+                }
+            }
+        };
+        let pointwise = body(
+            quote!("f64", pointwise),
+            item,
+            "lnlik_pointwise_log_likelihood",
+        );
+        assert!(pointwise.contains("compile_error"));
+    }
 }